@@ -0,0 +1,586 @@
+use agent_log_lib::config::Config;
+use agent_log_lib::index::SessionIndex;
+use agent_log_lib::query::SearchMode;
+use agent_log_lib::record::{ContentBlock, Record};
+use agent_log_lib::repos::repo_groups;
+use agent_log_lib::search::{search_page, SearchRequest, DEFAULT_PAGE_SIZE};
+use agent_log_lib::sources::{import_export, SourceRegistry};
+use agent_log_lib::stats::dashboard_stats;
+use agent_log_lib::subagents::session_subagents;
+use agent_log_lib::tree::{message_tree, TreeNode};
+use agent_log_lib::watch::watch_sessions;
+use agent_log_lib::{extract_text_content, BlockKind, Message};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Browse and search agent conversation history from the terminal.
+#[derive(Parser)]
+#[command(name = "agent-log-cli", version)]
+struct Cli {
+    /// Print machine-readable JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Include tool calls and their output when printing messages
+    #[arg(long, global = true)]
+    tools: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// List projects from every session source
+    Projects {
+        /// Group sessions by git repository and the directory they ran in
+        #[arg(long)]
+        by_repo: bool,
+    },
+    /// List sessions of a project, newest first
+    Sessions {
+        /// Project name or path
+        project: String,
+    },
+    /// Print the messages of a session
+    Show {
+        /// Session ID or path
+        session: String,
+    },
+    /// List every record of a session by kind, including non-message ones
+    Records {
+        /// Session ID or path
+        session: String,
+    },
+    /// Report the lines of a session that could not be read
+    Check {
+        /// Session ID or path
+        session: String,
+    },
+    /// Print the active branch of a session and where it forked
+    Tree {
+        /// Session ID or path
+        session: String,
+        /// Any message on the branch to show; defaults to the latest one
+        #[arg(long)]
+        leaf: Option<String>,
+    },
+    /// Print the conversations of the subagents a session spawned
+    Subagents {
+        /// Session ID or path
+        session: String,
+    },
+    /// Search message text across all sessions
+    Search {
+        query: String,
+        /// full-text, substring, case-sensitive, regex or whole-word
+        #[arg(long, default_value_t = SearchMode::FullText)]
+        mode: SearchMode,
+        /// Continue after a previous page, as printed at its end
+        #[arg(long)]
+        cursor: Option<String>,
+        /// Results per page
+        #[arg(long, default_value_t = DEFAULT_PAGE_SIZE)]
+        limit: usize,
+        /// Block kinds to search: text, tool_use, tool_result, thinking
+        #[arg(long, value_delimiter = ',', default_value = "text")]
+        blocks: Vec<BlockKind>,
+    },
+    /// Print token usage and activity totals
+    Stats,
+    /// Print changes to Claude Code and OpenCode sessions as they happen, as
+    /// one JSON event per line
+    Watch,
+    /// Save the output of `opencode export` so the session stays readable
+    /// after OpenCode deletes it
+    ImportOpencode {
+        /// File holding the export JSON
+        file: PathBuf,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let registry = SourceRegistry::discover();
+    let index = SessionIndex::open_default();
+
+    match run(&cli, &registry, &index, &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        // The reader, e.g. `head`, has all it wants.
+        Err(CliError::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Why a command failed: its own error, or a failed write to stdout.
+enum CliError {
+    Message(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Message(message) => f.write_str(message),
+            CliError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::Message(message)
+    }
+}
+
+impl From<&str> for CliError {
+    fn from(message: &str) -> Self {
+        CliError::Message(message.to_string())
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        CliError::Io(e)
+    }
+}
+
+fn run(
+    cli: &Cli,
+    registry: &SourceRegistry,
+    index: &SessionIndex,
+    out: &mut impl Write,
+) -> Result<(), CliError> {
+    match &cli.command {
+        Commands::Projects { by_repo: true } => {
+            let groups = repo_groups(registry, index)?;
+            if cli.json {
+                return print_json(out, &groups);
+            }
+
+            let mut rows = vec![];
+            for repo in &groups {
+                rows.push(vec![
+                    repo.name.clone(),
+                    repo.totals.session_count.to_string(),
+                    repo.totals.input_tokens.to_string(),
+                    repo.totals.output_tokens.to_string(),
+                    repo.remote.clone().unwrap_or_default(),
+                ]);
+                for workdir in &repo.workdirs {
+                    rows.push(vec![
+                        format!("  {}", workdir.name),
+                        workdir.totals.session_count.to_string(),
+                        workdir.totals.input_tokens.to_string(),
+                        workdir.totals.output_tokens.to_string(),
+                        String::new(),
+                    ]);
+                }
+            }
+
+            print_table(
+                out,
+                &["REPOSITORY", "SESSIONS", "INPUT", "OUTPUT", "REMOTE"],
+                rows.into_iter(),
+            )?;
+        }
+        Commands::Projects { by_repo: false } => {
+            let projects = registry.list_projects()?;
+            if cli.json {
+                return print_json(out, &projects);
+            }
+
+            print_table(
+                out,
+                &["NAME", "SESSIONS", "PATH"],
+                projects
+                    .iter()
+                    .map(|p| vec![p.name.clone(), p.session_count.to_string(), p.path.clone()]),
+            )?;
+        }
+        Commands::Sessions { project } => {
+            let project = registry.find_project(project)?;
+            let source = registry.source_for(Path::new(&project.path))?;
+            let sessions = index.list_sessions(source, &project)?;
+            if cli.json {
+                return print_json(out, &sessions);
+            }
+
+            print_table(
+                out,
+                &["ID", "MODIFIED", "INPUT", "OUTPUT", "COST"],
+                sessions.iter().map(|s| {
+                    vec![
+                        s.id.clone(),
+                        format_unix(s.modified),
+                        s.input_tokens.to_string(),
+                        s.output_tokens.to_string(),
+                        s.cost.map(|c| format!("${:.2}", c)).unwrap_or_default(),
+                    ]
+                }),
+            )?;
+        }
+        Commands::Show { session } => {
+            let path = registry.find_session(session)?;
+            let messages = registry.source_for(&path)?.load_messages(&path)?;
+            if cli.json {
+                return print_json(out, &messages);
+            }
+
+            for msg in &messages {
+                print_message(out, msg, cli.tools)?;
+            }
+        }
+        Commands::Records { session } => {
+            let path = registry.find_session(session)?;
+            let records = registry.source_for(&path)?.load_records(&path)?;
+            if cli.json {
+                return print_json(out, &records);
+            }
+
+            print_table(
+                out,
+                &["TIMESTAMP", "KIND", "DETAIL"],
+                records.iter().map(|r| {
+                    let (kind, detail) = describe_record(r);
+                    vec![
+                        r.timestamp().unwrap_or("").to_string(),
+                        kind.to_string(),
+                        detail,
+                    ]
+                }),
+            )?;
+        }
+        Commands::Check { session } => {
+            let path = registry.find_session(session)?;
+            let errors = registry.source_for(&path)?.line_errors(&path)?;
+            if cli.json {
+                return print_json(out, &errors);
+            }
+
+            if errors.is_empty() {
+                writeln!(out, "Every line was read.")?;
+            }
+            for error in &errors {
+                writeln!(out, "line {}: {}", error.line, error.message)?;
+            }
+        }
+        Commands::Tree { session, leaf } => {
+            let path = registry.find_session(session)?;
+            let tree = message_tree(registry, &path, leaf.as_deref())?;
+            if cli.json {
+                return print_json(out, &tree);
+            }
+
+            let nodes: HashMap<&str, &TreeNode> =
+                tree.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+
+            for (i, id) in tree.active_path.iter().enumerate() {
+                let node = nodes[id.as_str()];
+                print_message(out, &node.message, cli.tools)?;
+
+                if node.is_branch_point {
+                    let next = tree.active_path.get(i + 1);
+                    for child in node.children.iter().filter(|c| Some(*c) != next) {
+                        let text = extract_text_content(&nodes[child.as_str()].message.message);
+                        let preview: String = text.trim().chars().take(60).collect();
+                        writeln!(
+                            out,
+                            "    other branch: --leaf {}  {}",
+                            child,
+                            preview.replace('\n', " ")
+                        )?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+        }
+        Commands::Subagents { session } => {
+            let path = registry.find_session(session)?;
+            let subagents = session_subagents(registry, &path)?;
+            if cli.json {
+                return print_json(out, &subagents);
+            }
+
+            for subagent in &subagents {
+                writeln!(
+                    out,
+                    "=== {} ({}) tool_use {}",
+                    subagent.description.as_deref().unwrap_or("Subagent"),
+                    subagent.subagent_type.as_deref().unwrap_or("unknown type"),
+                    subagent.tool_use_id.as_deref().unwrap_or("unmatched")
+                )?;
+                writeln!(out)?;
+                for msg in &subagent.messages {
+                    print_message(out, msg, cli.tools)?;
+                }
+            }
+        }
+        Commands::Search {
+            query,
+            mode,
+            cursor,
+            limit,
+            blocks,
+        } => {
+            let request = SearchRequest {
+                query: query.clone(),
+                mode: *mode,
+                cursor: cursor.clone(),
+                limit: *limit,
+                blocks: blocks.clone(),
+            };
+            let page = search_page(registry, index, &request).map_err(|e| e.to_string())?;
+            if cli.json {
+                return print_json(out, &page);
+            }
+
+            print_table(
+                out,
+                &[
+                    "TIMESTAMP",
+                    "ROLE",
+                    "PROJECT",
+                    "SESSION",
+                    "BLOCK",
+                    "PREVIEW",
+                ],
+                page.results.iter().map(|r| {
+                    vec![
+                        r.timestamp.clone(),
+                        r.role.clone(),
+                        r.project_name.clone(),
+                        r.session_id.clone(),
+                        r.block.as_str().to_string(),
+                        r.content_preview.clone(),
+                    ]
+                }),
+            )?;
+
+            if let Some(next) = &page.next_cursor {
+                writeln!(out, "\nMore results: --cursor '{}'", next)?;
+            }
+        }
+        Commands::Stats => {
+            let stats = dashboard_stats(registry, index)?;
+            if cli.json {
+                return print_json(out, &stats);
+            }
+
+            writeln!(out, "Sessions:       {}", stats.total_sessions)?;
+            writeln!(out, "Messages:       {}", stats.total_messages)?;
+            writeln!(out, "Input tokens:   {}", stats.total_input_tokens)?;
+            writeln!(out, "Output tokens:  {}", stats.total_output_tokens)?;
+            writeln!(out, "Reasoning:      {}", stats.total_reasoning_tokens)?;
+            writeln!(out, "Cache read:     {}", stats.total_cache_read_tokens)?;
+            writeln!(out, "Cache write:    {}", stats.total_cache_write_tokens)?;
+            writeln!(
+                out,
+                "Estimated cost: ${:.2} (${:.2} recorded)",
+                stats.estimated_cost, stats.recorded_cost
+            )?;
+            writeln!(out, "Avg session:    {:.1} min", stats.avg_session_minutes)?;
+            writeln!(out)?;
+
+            print_table(
+                out,
+                &["PROJECT", "SESSIONS", "INPUT", "OUTPUT"],
+                stats.project_stats.iter().map(|p| {
+                    vec![
+                        p.name.clone(),
+                        p.session_count.to_string(),
+                        p.total_input_tokens.to_string(),
+                        p.total_output_tokens.to_string(),
+                    ]
+                }),
+            )?;
+        }
+        Commands::Watch => {
+            watch_sessions(registry, |event| {
+                let Ok(json) = serde_json::to_string(&event) else {
+                    return;
+                };
+                if let Err(e) = writeln!(out, "{}", json).and_then(|()| out.flush()) {
+                    if e.kind() == io::ErrorKind::BrokenPipe {
+                        std::process::exit(0);
+                    }
+                    eprintln!("error: {}", e);
+                }
+            })?;
+        }
+        Commands::ImportOpencode { file } => {
+            let export_dir = Config::load()
+                .opencode
+                .export_dir()
+                .ok_or("Could not determine the OpenCode export directory")?;
+            let saved = import_export(file, &export_dir).map_err(|e| e.to_string())?;
+            writeln!(out, "Saved to {}", saved.display())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn print_json<T: Serialize>(out: &mut impl Write, value: &T) -> Result<(), CliError> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    writeln!(out, "{}", json)?;
+    Ok(())
+}
+
+/// Prints a message, or nothing for one that only calls tools when `tools`
+/// is off.
+fn print_message(out: &mut impl Write, msg: &Message, tools: bool) -> io::Result<()> {
+    let text = extract_text_content(&msg.message);
+    if !tools && text.trim().is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "--- {} [{}]",
+        msg.role().unwrap_or("unknown"),
+        msg.timestamp.as_deref().unwrap_or("")
+    )?;
+    if !text.trim().is_empty() {
+        writeln!(out, "{}", text.trim())?;
+    }
+
+    if tools {
+        for call in &msg.tool_calls {
+            let status = match (&call.output, call.is_error) {
+                (None, _) => "no result",
+                (Some(_), true) => "error",
+                (Some(_), false) => "ok",
+            };
+            let duration = call
+                .duration_ms
+                .map(|ms| format!(", {:.1}s", ms as f64 / 1000.0))
+                .unwrap_or_default();
+            writeln!(out, "[tool] {} ({}{})", call.name, status, duration)?;
+            writeln!(out, "  input:  {}", call.input)?;
+            if let Some(output) = &call.output {
+                let preview: String = output.trim().chars().take(200).collect();
+                writeln!(out, "  output: {}", preview.replace('\n', "\n          "))?;
+            }
+        }
+    }
+    writeln!(out)
+}
+
+fn describe_record(record: &Record) -> (&'static str, String) {
+    match record {
+        Record::User(r) | Record::Assistant(r) => {
+            let kind = if matches!(record, Record::User(_)) {
+                "user"
+            } else {
+                "assistant"
+            };
+            let blocks: Vec<&str> = r
+                .content
+                .iter()
+                .map(|block| match block {
+                    ContentBlock::Text { .. } => "text",
+                    ContentBlock::Thinking { .. } => "thinking",
+                    ContentBlock::ToolUse { .. } => "tool_use",
+                    ContentBlock::ToolResult { .. } => "tool_result",
+                    ContentBlock::Image { .. } => "image",
+                    ContentBlock::Unknown { .. } => "unknown",
+                })
+                .collect();
+            (kind, blocks.join(", "))
+        }
+        Record::Summary(r) => ("summary", r.summary.clone()),
+        Record::System(r) => (
+            "system",
+            format!(
+                "{}: {}",
+                r.subtype.as_deref().unwrap_or("-"),
+                r.content.as_deref().unwrap_or("")
+            ),
+        ),
+        Record::CompactBoundary(r) => {
+            let metadata = r.metadata.as_ref();
+            (
+                "compact_boundary",
+                format!(
+                    "{} compact, {} tokens before",
+                    metadata
+                        .and_then(|m| m.trigger.as_deref())
+                        .unwrap_or("unknown"),
+                    metadata.and_then(|m| m.pre_tokens).unwrap_or(0)
+                ),
+            )
+        }
+        Record::FileHistorySnapshot(r) => (
+            "file_history_snapshot",
+            format!(
+                "{} files",
+                r.snapshot
+                    .as_ref()
+                    .map_or(0, |s| s.tracked_file_backups.len())
+            ),
+        ),
+        Record::Unknown { raw } => (
+            "unknown",
+            raw.get("type")
+                .and_then(|t| t.as_str())
+                .unwrap_or("")
+                .to_string(),
+        ),
+    }
+}
+
+/// Prints left-aligned columns sized to their widest cell. The last column is
+/// left unpadded so long previews and paths do not produce trailing spaces.
+fn print_table(
+    out: &mut impl Write,
+    headers: &[&str],
+    rows: impl Iterator<Item = Vec<String>>,
+) -> io::Result<()> {
+    let rows: Vec<Vec<String>> = rows
+        .map(|row| {
+            row.into_iter()
+                .map(|cell| cell.replace('\n', " "))
+                .collect()
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header_row: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+    for row in std::iter::once(&header_row).chain(&rows) {
+        let last = row.len().saturating_sub(1);
+        let line: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                if i == last {
+                    cell.clone()
+                } else {
+                    format!("{:<width$}", cell, width = widths[i])
+                }
+            })
+            .collect();
+        writeln!(out, "{}", line.join("  "))?;
+    }
+    Ok(())
+}
+
+fn format_unix(secs: u64) -> String {
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
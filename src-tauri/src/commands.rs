@@ -0,0 +1,178 @@
+use crate::context::{self, SessionContext};
+use crate::index::SessionIndex;
+use crate::models::{Message, Project, Session};
+use crate::query::SearchMode;
+use crate::record::Record;
+use crate::repos::{self, RepoGroup};
+use crate::search::{
+    self, SearchCancellation, SearchError, SearchEvent, SearchRequest, SearchResult,
+};
+use crate::sources::{is_visible, LineError, OpenCodeError, SourceRegistry};
+use crate::stats::{self, DashboardStats};
+use crate::subagents::{self, Subagent};
+use crate::tree::{self, MessageTree};
+use std::path::{Path, PathBuf};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager, State};
+
+#[tauri::command]
+pub fn get_projects(registry: State<'_, SourceRegistry>) -> Result<Vec<Project>, String> {
+    registry.list_projects()
+}
+
+/// Sessions of every project regrouped by git repository, then by the
+/// worktree or subdirectory each one ran in.
+#[tauri::command]
+pub fn get_project_groups(
+    registry: State<'_, SourceRegistry>,
+    index: State<'_, SessionIndex>,
+) -> Result<Vec<RepoGroup>, String> {
+    repos::repo_groups(&registry, &index)
+}
+
+#[tauri::command]
+pub fn get_sessions(
+    registry: State<'_, SourceRegistry>,
+    index: State<'_, SessionIndex>,
+    project_path: String,
+) -> Result<Vec<Session>, String> {
+    let (source, project) = registry.project_at(Path::new(&project_path))?;
+
+    index.list_sessions(source, &project)
+}
+
+#[tauri::command]
+pub fn get_messages(
+    registry: State<'_, SourceRegistry>,
+    session_path: String,
+) -> Result<Vec<Message>, String> {
+    let path = PathBuf::from(&session_path);
+
+    registry.source_for(&path)?.load_messages(&path)
+}
+
+/// Every record of a session, including the summaries, system notices and
+/// snapshots `get_messages` leaves out.
+#[tauri::command]
+pub fn get_records(
+    registry: State<'_, SourceRegistry>,
+    session_path: String,
+) -> Result<Vec<Record>, String> {
+    let path = PathBuf::from(&session_path);
+
+    registry.source_for(&path)?.load_records(&path)
+}
+
+/// Lines of a session that could not be read, for checking a custom mapping.
+#[tauri::command]
+pub fn get_line_errors(
+    registry: State<'_, SourceRegistry>,
+    session_path: String,
+) -> Result<Vec<LineError>, String> {
+    let path = PathBuf::from(&session_path);
+
+    registry.source_for(&path)?.line_errors(&path)
+}
+
+#[tauri::command]
+pub fn get_message_tree(
+    registry: State<'_, SourceRegistry>,
+    session_path: String,
+    active_leaf: Option<String>,
+) -> Result<MessageTree, String> {
+    tree::message_tree(
+        &registry,
+        &PathBuf::from(&session_path),
+        active_leaf.as_deref(),
+    )
+}
+
+#[tauri::command]
+pub fn get_subagents(
+    registry: State<'_, SourceRegistry>,
+    session_path: String,
+) -> Result<Vec<Subagent>, String> {
+    subagents::session_subagents(&registry, &PathBuf::from(&session_path))
+}
+
+#[tauri::command]
+pub fn get_opencode_messages(
+    registry: State<'_, SourceRegistry>,
+    session_id: String,
+) -> Result<Vec<Message>, OpenCodeError> {
+    let source = registry.opencode().ok_or_else(|| OpenCodeError::Io {
+        message: "OpenCode storage is not available".to_string(),
+    })?;
+
+    Ok(source
+        .load_session(&session_id)?
+        .into_iter()
+        .filter(is_visible)
+        .collect())
+}
+
+#[tauri::command]
+pub fn search_messages(
+    registry: State<'_, SourceRegistry>,
+    index: State<'_, SessionIndex>,
+    query: String,
+    mode: Option<SearchMode>,
+) -> Result<Vec<SearchResult>, SearchError> {
+    search::search_messages(&registry, &index, &query, mode.unwrap_or_default())
+}
+
+/// Streams one page of results over `on_event`. Starting another search, or
+/// calling `cancel_search`, stops this one at its next batch.
+#[tauri::command]
+pub async fn stream_search_messages(
+    app: AppHandle,
+    request: SearchRequest,
+    on_event: Channel<SearchEvent>,
+) -> Result<(), SearchError> {
+    // Syncing the index and reading sessions block, so the search runs on a
+    // blocking thread rather than the async runtime's.
+    tauri::async_runtime::spawn_blocking(move || {
+        let registry = app.state::<SourceRegistry>();
+        let index = app.state::<SessionIndex>();
+        let cancellation = app.state::<SearchCancellation>();
+        let generation = cancellation.begin();
+
+        let next_cursor = search::stream_search(&registry, &index, &request, &mut |results| {
+            cancellation.is_current(generation)
+                && on_event.send(SearchEvent::Results { results }).is_ok()
+        })?;
+
+        let event = if cancellation.is_current(generation) {
+            SearchEvent::Finished { next_cursor }
+        } else {
+            SearchEvent::Cancelled
+        };
+
+        on_event
+            .send(event)
+            .map_err(|e| SearchError::from(e.to_string()))
+    })
+    .await
+    .map_err(|e| SearchError::from(e.to_string()))?
+}
+
+#[tauri::command]
+pub fn cancel_search(cancellation: State<'_, SearchCancellation>) {
+    cancellation.cancel();
+}
+
+#[tauri::command]
+pub fn get_session_context(
+    registry: State<'_, SourceRegistry>,
+    session_path: String,
+) -> Result<SessionContext, String> {
+    context::session_context(&registry, &PathBuf::from(&session_path))
+}
+
+#[tauri::command]
+pub fn get_dashboard_stats(
+    registry: State<'_, SourceRegistry>,
+    index: State<'_, SessionIndex>,
+) -> Result<DashboardStats, String> {
+    stats::dashboard_stats(&registry, &index)
+}
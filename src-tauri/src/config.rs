@@ -0,0 +1,100 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// Settings read from `agent-log/config.json` in the user's config
+/// directory, e.g. `~/.config/agent-log/config.json` on Linux. The file and
+/// every field in it are optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub opencode: OpenCodeConfig,
+    pub aider: AiderConfig,
+    pub cline: ClineConfig,
+    pub roo_code: ClineConfig,
+    /// Mapping files of JSONL logs to read, see [`crate::sources::Mapping`].
+    pub custom: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OpenCodeConfig {
+    /// The `opencode` binary. Looked up on `PATH`, then in
+    /// `~/.opencode/bin`, when unset.
+    pub bin: Option<PathBuf>,
+    /// Saved `opencode export` output, one `<session_id>.json` per session.
+    pub export_dir: Option<PathBuf>,
+    /// How long `opencode export` may run before it is killed.
+    pub export_timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AiderConfig {
+    /// Repositories to read `.aider.chat.history.md` from; Aider writes it
+    /// into the repository it runs in.
+    pub repos: Vec<PathBuf>,
+}
+
+/// Cline, or a fork of it like Roo Code.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ClineConfig {
+    /// The extension's `tasks` directory. Defaults to the one in VS Code's
+    /// global storage.
+    pub tasks_dir: Option<PathBuf>,
+}
+
+impl ClineConfig {
+    pub fn tasks_dir(&self, extension_id: &str) -> Option<PathBuf> {
+        self.tasks_dir.clone().or_else(|| {
+            dirs::config_dir().map(|d| {
+                d.join("Code/User/globalStorage")
+                    .join(extension_id)
+                    .join("tasks")
+            })
+        })
+    }
+}
+
+impl Default for OpenCodeConfig {
+    fn default() -> Self {
+        Self {
+            bin: None,
+            export_dir: None,
+            export_timeout_secs: 30,
+        }
+    }
+}
+
+impl OpenCodeConfig {
+    /// The configured export directory, or `agent-log/opencode-exports` in
+    /// the user's data directory.
+    pub fn export_dir(&self) -> Option<PathBuf> {
+        self.export_dir
+            .clone()
+            .or_else(|| dirs::data_dir().map(|d| d.join("agent-log").join("opencode-exports")))
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("agent-log").join("config.json"))
+    }
+
+    /// Reads the config file, falling back to the defaults when it is missing
+    /// or invalid.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("Ignoring invalid config {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+}
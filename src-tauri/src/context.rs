@@ -0,0 +1,157 @@
+use crate::sources::SourceRegistry;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+pub struct FileChange {
+    pub file_path: String,
+    pub action: String,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GitCommit {
+    pub hash: String,
+    pub message: String,
+    pub timestamp: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionContext {
+    pub file_changes: Vec<FileChange>,
+    pub git_commits: Vec<GitCommit>,
+    /// Where the session ran, when its source records it.
+    pub project_path: Option<String>,
+}
+
+pub fn session_context(
+    registry: &SourceRegistry,
+    session_path: &Path,
+) -> Result<SessionContext, String> {
+    let path = session_path;
+
+    let mut file_changes: Vec<FileChange> = vec![];
+    let mut timestamps: Vec<String> = vec![];
+
+    for msg in registry.source_for(path)?.records(path)? {
+        let timestamp = msg.timestamp.clone().unwrap_or_default();
+
+        if !timestamp.is_empty() {
+            timestamps.push(timestamp.clone());
+        }
+
+        let content_arr = match msg
+            .message
+            .as_ref()
+            .and_then(|m| m.content.as_ref())
+            .and_then(|c| c.as_array())
+        {
+            Some(arr) => arr,
+            None => continue,
+        };
+
+        for item in content_arr {
+            if item.get("type").and_then(|t| t.as_str()) != Some("tool_use") {
+                continue;
+            }
+
+            let tool_name = item.get("name").and_then(|n| n.as_str()).unwrap_or("");
+            let input = item.get("input");
+
+            let file_path = match tool_name {
+                "Edit" | "Write" => input
+                    .and_then(|i| i.get("file_path"))
+                    .and_then(|f| f.as_str()),
+                "mcp_edit" | "mcp_write" => input
+                    .and_then(|i| i.get("filePath"))
+                    .and_then(|f| f.as_str()),
+                _ => None,
+            };
+
+            if let Some(fp) = file_path {
+                file_changes.push(FileChange {
+                    file_path: fp.to_string(),
+                    action: tool_name.to_string(),
+                    timestamp: timestamp.clone(),
+                });
+            }
+        }
+    }
+
+    let project_path = registry.working_dir(path);
+    let git_commits = match &project_path {
+        Some(dir) => get_git_commits(dir, &timestamps),
+        None => vec![],
+    };
+
+    Ok(SessionContext {
+        file_changes,
+        git_commits,
+        project_path: project_path.map(|p| p.to_string_lossy().to_string()),
+    })
+}
+
+fn get_git_commits(project_path: &Path, timestamps: &[String]) -> Vec<GitCommit> {
+    if timestamps.is_empty() {
+        return vec![];
+    }
+
+    let start_time = timestamps.iter().min().cloned().unwrap_or_default();
+    let end_time = timestamps.iter().max().cloned().unwrap_or_default();
+
+    if start_time.is_empty() || end_time.is_empty() {
+        return vec![];
+    }
+
+    let output = match std::process::Command::new("git")
+        .args([
+            "log",
+            "--format=%H|%s|%aI",
+            "--name-only",
+            &format!("--since={}", start_time),
+            &format!("--until={}", end_time),
+        ])
+        .current_dir(project_path)
+        .output()
+    {
+        Ok(o) => o,
+        Err(_) => return vec![],
+    };
+
+    if !output.status.success() {
+        return vec![];
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut commits = vec![];
+    let mut current_commit: Option<GitCommit> = None;
+
+    for line in stdout.lines() {
+        if line.contains('|') {
+            if let Some(commit) = current_commit.take() {
+                commits.push(commit);
+            }
+
+            let parts: Vec<&str> = line.splitn(3, '|').collect();
+            if parts.len() >= 3 {
+                current_commit = Some(GitCommit {
+                    hash: parts[0].to_string(),
+                    message: parts[1].to_string(),
+                    timestamp: parts[2].to_string(),
+                    files: vec![],
+                });
+            }
+        } else if !line.trim().is_empty() {
+            if let Some(ref mut commit) = current_commit {
+                commit.files.push(line.trim().to_string());
+            }
+        }
+    }
+
+    if let Some(commit) = current_commit {
+        commits.push(commit);
+    }
+
+    commits
+}
@@ -0,0 +1,794 @@
+use crate::models::{extract_block_text, BlockKind, Project, Session, UsageTotals};
+use crate::query::{Filter, SearchMode, SearchQuery};
+use crate::sources::{is_displayable, Records, SessionSource, SessionStamp, SourceRegistry};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+
+/// Bump whenever the schema or the way records are extracted changes; older
+/// caches are dropped and rebuilt on open.
+const SCHEMA_VERSION: i32 = 7;
+
+/// Rows read per query while searching.
+const SEARCH_BATCH: usize = 200;
+
+const SCHEMA: &str = "
+CREATE TABLE sessions (
+    path TEXT PRIMARY KEY,
+    source TEXT NOT NULL,
+    project_name TEXT NOT NULL,
+    project_path TEXT NOT NULL,
+    session_id TEXT NOT NULL,
+    title TEXT,
+    size INTEGER NOT NULL,
+    mtime_ms INTEGER NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    reasoning_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_write_tokens INTEGER NOT NULL DEFAULT 0,
+    -- Recorded cost in USD; NULL when no message recorded one
+    cost REAL,
+    message_count INTEGER NOT NULL,
+    first_timestamp TEXT,
+    last_timestamp TEXT
+);
+
+CREATE INDEX sessions_project ON sessions(project_path);
+
+CREATE TABLE messages (
+    session_path TEXT NOT NULL REFERENCES sessions(path) ON DELETE CASCADE,
+    seq INTEGER NOT NULL,
+    uuid TEXT,
+    role TEXT,
+    timestamp TEXT,
+    model TEXT,
+    -- Called tool names wrapped in commas, e.g. `,Bash,Read,`
+    tools TEXT NOT NULL,
+    is_conversation INTEGER NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    -- Text of each searchable block kind, see `BlockKind`
+    text TEXT NOT NULL,
+    tool_use TEXT NOT NULL,
+    tool_result TEXT NOT NULL,
+    thinking TEXT NOT NULL,
+    PRIMARY KEY (session_path, seq)
+);
+
+CREATE VIRTUAL TABLE messages_fts USING fts5(
+    text,
+    tool_use,
+    tool_result,
+    thinking,
+    content = 'messages',
+    content_rowid = 'rowid',
+    tokenize = 'porter unicode61'
+);
+
+CREATE TRIGGER messages_fts_insert AFTER INSERT ON messages
+WHEN new.is_conversation = 1
+BEGIN
+    INSERT INTO messages_fts (rowid, text, tool_use, tool_result, thinking)
+    VALUES (new.rowid, new.text, new.tool_use, new.tool_result, new.thinking);
+END;
+
+CREATE TRIGGER messages_fts_delete AFTER DELETE ON messages
+WHEN old.is_conversation = 1
+BEGIN
+    INSERT INTO messages_fts (messages_fts, rowid, text, tool_use, tool_result, thinking)
+    VALUES ('delete', old.rowid, old.text, old.tool_use, old.tool_result, old.thinking);
+END;
+";
+
+/// Per-session row of the index.
+pub struct IndexedSession {
+    pub path: String,
+    pub project_name: String,
+    pub project_path: String,
+    pub usage: UsageTotals,
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+}
+
+/// Token and timing data of one record, without its text.
+pub struct MessageActivity {
+    pub timestamp: Option<String>,
+    pub is_conversation: bool,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// Text of one block kind of a message.
+pub type BlockText = (BlockKind, String);
+
+/// A conversation message whose text matched a search.
+pub struct MessageHit {
+    /// BM25 relevance, higher is better.
+    pub score: f64,
+    pub project_name: String,
+    pub project_path: String,
+    pub session_id: String,
+    pub session_path: String,
+    pub uuid: Option<String>,
+    pub role: Option<String>,
+    pub timestamp: Option<String>,
+    /// Non-empty text of each searched block kind.
+    pub blocks: Vec<BlockText>,
+    /// Resumes a search right after this hit.
+    pub cursor: SearchCursor,
+}
+
+/// Position in a search's result order, written as `<rowid>:<sort key>`.
+#[derive(Debug, Clone)]
+pub struct SearchCursor {
+    key: String,
+    rowid: i64,
+}
+
+impl fmt::Display for SearchCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.rowid, self.key)
+    }
+}
+
+impl FromStr for SearchCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rowid, key) = s.split_once(':').ok_or("Invalid search cursor")?;
+        Ok(Self {
+            key: key.to_string(),
+            rowid: rowid.parse().map_err(|_| "Invalid search cursor")?,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Ascending,
+    Descending,
+}
+
+impl Direction {
+    fn keyword(self) -> &'static str {
+        match self {
+            Direction::Ascending => "ASC",
+            Direction::Descending => "DESC",
+        }
+    }
+
+    fn operator(self) -> &'static str {
+        match self {
+            Direction::Ascending => ">",
+            Direction::Descending => "<",
+        }
+    }
+}
+
+/// SQLite cache of everything the dashboard, search and session list need, so
+/// session files are only parsed again when their size or mtime changes.
+pub struct SessionIndex {
+    conn: Mutex<Connection>,
+}
+
+impl SessionIndex {
+    /// Opens the cache in the user's cache directory, falling back to an
+    /// in-memory database when it cannot be created.
+    pub fn open_default() -> Self {
+        let on_disk = default_index_path().and_then(|path| {
+            fs::create_dir_all(path.parent()?).ok()?;
+            Self::open(&path).ok()
+        });
+
+        on_disk.unwrap_or_else(|| Self::open_in_memory().expect("in-memory SQLite is available"))
+    }
+
+    pub fn open(path: &Path) -> Result<Self, String> {
+        Self::init(Connection::open(path).map_err(|e| e.to_string())?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, String> {
+        Self::init(Connection::open_in_memory().map_err(|e| e.to_string())?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")
+            .map_err(|e| e.to_string())?;
+
+        let version: i32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+
+        if version != SCHEMA_VERSION {
+            conn.execute_batch(&format!(
+                "DROP TABLE IF EXISTS messages_fts;
+                 DROP TABLE IF EXISTS messages;
+                 DROP TABLE IF EXISTS sessions;
+                 {}
+                 PRAGMA user_version = {};",
+                SCHEMA, SCHEMA_VERSION
+            ))
+            .map_err(|e| e.to_string())?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Brings every session of every source up to date and forgets sessions
+    /// whose files are gone. A source that cannot be listed is reported and
+    /// its sessions are kept as they were indexed.
+    pub fn sync(&self, registry: &SourceRegistry) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        let mut skipped = HashSet::new();
+
+        for source in registry.iter() {
+            let projects = match source.list_projects() {
+                Ok(projects) => projects,
+                Err(e) => {
+                    eprintln!("Skipping {} projects: {}", source.id(), e);
+                    skipped.insert(source.id().to_string());
+                    continue;
+                }
+            };
+
+            for project in projects {
+                let session_paths = match source.list_session_paths(Path::new(&project.path)) {
+                    Ok(paths) => paths,
+                    Err(_) => continue,
+                };
+
+                for session_path in session_paths {
+                    self.refresh(source, &project, &session_path)?;
+                    seen.insert(session_path.to_string_lossy().to_string());
+                }
+            }
+        }
+
+        let conn = self.conn();
+        let indexed: Vec<(String, String)> = conn
+            .prepare("SELECT path, source FROM sessions")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect::<Result<_, _>>()
+            })
+            .map_err(|e| e.to_string())?;
+
+        let gone = indexed
+            .iter()
+            .filter(|(path, source)| !seen.contains(path) && !skipped.contains(source))
+            .map(|(path, _)| path);
+        for path in gone {
+            conn.execute("DELETE FROM sessions WHERE path = ?1", [path])
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    /// Sessions of a single project with at least one displayable message,
+    /// newest first.
+    pub fn list_sessions(
+        &self,
+        source: &dyn SessionSource,
+        project: &Project,
+    ) -> Result<Vec<Session>, String> {
+        let session_paths = source.list_session_paths(Path::new(&project.path))?;
+        for session_path in &session_paths {
+            self.refresh(source, project, session_path)?;
+        }
+
+        let current: HashSet<String> = session_paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, session_id, title, size, mtime_ms, input_tokens, output_tokens,
+                        reasoning_tokens, cache_read_tokens, cache_write_tokens, cost
+                 FROM sessions
+                 WHERE project_path = ?1 AND message_count > 0
+                 ORDER BY mtime_ms DESC",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let sessions = stmt
+            .query_map([&project.path], |row| {
+                Ok(Session {
+                    path: row.get(0)?,
+                    id: row.get(1)?,
+                    title: row.get(2)?,
+                    size: row.get(3)?,
+                    modified: row.get::<_, u64>(4)? / 1000,
+                    input_tokens: row.get(5)?,
+                    output_tokens: row.get(6)?,
+                    reasoning_tokens: row.get(7)?,
+                    cache_read_tokens: row.get(8)?,
+                    cache_write_tokens: row.get(9)?,
+                    cost: row.get(10)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| e.to_string())?;
+
+        Ok(sessions
+            .into_iter()
+            .filter(|s| current.contains(&s.path))
+            .collect())
+    }
+
+    pub fn sessions(&self) -> Result<Vec<IndexedSession>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare(
+                "SELECT path, project_name, project_path, input_tokens, output_tokens,
+                        reasoning_tokens, cache_read_tokens, cache_write_tokens, cost,
+                        first_timestamp, last_timestamp
+                 FROM sessions",
+            )
+            .map_err(|e| e.to_string())?;
+
+        stmt.query_map([], |row| {
+            Ok(IndexedSession {
+                path: row.get(0)?,
+                project_name: row.get(1)?,
+                project_path: row.get(2)?,
+                usage: UsageTotals {
+                    input_tokens: row.get(3)?,
+                    output_tokens: row.get(4)?,
+                    reasoning_tokens: row.get(5)?,
+                    cache_read_tokens: row.get(6)?,
+                    cache_write_tokens: row.get(7)?,
+                    cost: row.get(8)?,
+                },
+                first_timestamp: row.get(9)?,
+                last_timestamp: row.get(10)?,
+            })
+        })
+        .and_then(|rows| rows.collect())
+        .map_err(|e| e.to_string())
+    }
+
+    pub fn message_activity(&self) -> Result<Vec<MessageActivity>, String> {
+        let conn = self.conn();
+        let mut stmt = conn
+            .prepare("SELECT timestamp, is_conversation, input_tokens, output_tokens FROM messages")
+            .map_err(|e| e.to_string())?;
+
+        stmt.query_map([], |row| {
+            Ok(MessageActivity {
+                timestamp: row.get(0)?,
+                is_conversation: row.get(1)?,
+                input_tokens: row.get(2)?,
+                output_tokens: row.get(3)?,
+            })
+        })
+        .and_then(|rows| rows.collect())
+        .map_err(|e| e.to_string())
+    }
+
+    /// Conversation messages matching a parsed query, most relevant first, or
+    /// newest first when the query only has filters. Hits are handed to
+    /// `on_hit` as they are read until it returns `false`.
+    ///
+    /// Outside full-text mode the text is not looked up in the FTS index;
+    /// `accept` is called on every message that passes the filters instead.
+    pub fn search(
+        &self,
+        query: &SearchQuery,
+        after: Option<&SearchCursor>,
+        accept: &dyn Fn(&[BlockText]) -> bool,
+        on_hit: &mut dyn FnMut(MessageHit) -> bool,
+    ) -> Result<(), String> {
+        let mut args: Vec<Value> = vec![];
+        let mut clauses = vec!["m.is_conversation = 1".to_string()];
+        let full_text = query.mode == SearchMode::FullText;
+        let kinds = query.searched_blocks();
+        // A query of only filters or exclusions still needs text to show.
+        clauses.push(format!(
+            "({})",
+            kinds
+                .iter()
+                .map(|k| format!("m.{} != ''", k.as_str()))
+                .collect::<Vec<_>>()
+                .join(" OR ")
+        ));
+        // FTS5 column filter, e.g. `{text tool_use}`
+        let columns = format!(
+            "{{{}}}",
+            kinds
+                .iter()
+                .map(|k| k.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+
+        // Hits are ordered by (sort_key, rowid) so a cursor can resume after
+        // the last one even when several share a rank or timestamp.
+        let (from, rank, sort_key, direction) = match query.fts_match().filter(|_| full_text) {
+            Some(expr) => {
+                args.push(Value::Text(format!("{} : ({})", columns, expr)));
+                clauses.push(format!("messages_fts MATCH ?{}", args.len()));
+                (
+                    "messages_fts JOIN messages m ON m.rowid = messages_fts.rowid",
+                    "bm25(messages_fts)",
+                    "bm25(messages_fts)",
+                    Direction::Ascending,
+                )
+            }
+            None => (
+                "messages m",
+                "0.0",
+                "coalesce(m.timestamp, '')",
+                Direction::Descending,
+            ),
+        };
+
+        if let Some(expr) = query.fts_excluded().filter(|_| full_text) {
+            args.push(Value::Text(format!("{} : ({})", columns, expr)));
+            clauses.push(format!(
+                "m.rowid NOT IN (SELECT rowid FROM messages_fts WHERE messages_fts MATCH ?{})",
+                args.len()
+            ));
+        }
+
+        for filter in &query.filters {
+            let (clause, value) = match filter {
+                Filter::Role(role) => ("lower(m.role) = ?N", role.clone()),
+                Filter::Project(project) => (
+                    "(instr(lower(s.project_name), lower(?N)) > 0
+                      OR instr(lower(s.project_path), lower(?N)) > 0)",
+                    project.clone(),
+                ),
+                Filter::After(date) => (
+                    "substr(m.timestamp, 1, 10) >= ?N",
+                    date.format("%Y-%m-%d").to_string(),
+                ),
+                Filter::Before(date) => (
+                    "substr(m.timestamp, 1, 10) < ?N",
+                    date.format("%Y-%m-%d").to_string(),
+                ),
+                Filter::Tool(tool) => (
+                    "instr(lower(m.tools), ',' || lower(?N) || ',') > 0",
+                    tool.clone(),
+                ),
+                Filter::Model(model) => ("instr(lower(m.model), lower(?N)) > 0", model.clone()),
+            };
+
+            args.push(Value::Text(value));
+            let placeholder = format!("?{}", args.len());
+            clauses.push(clause.replace("?N", &placeholder));
+        }
+
+        // Rows are read a batch at a time, so the connection is not held
+        // while hits are handed on.
+        let mut after = after.cloned();
+        loop {
+            let mut batch_args = args.clone();
+            let mut batch_clauses = clauses.clone();
+
+            if let Some(cursor) = &after {
+                let key = match direction {
+                    Direction::Ascending => Value::Real(
+                        cursor
+                            .key
+                            .parse()
+                            .map_err(|_| "Invalid search cursor".to_string())?,
+                    ),
+                    Direction::Descending => Value::Text(cursor.key.clone()),
+                };
+                batch_args.push(key);
+                batch_args.push(Value::Integer(cursor.rowid));
+                batch_clauses.push(format!(
+                    "({key} {op} ?{k} OR ({key} = ?{k} AND m.rowid {op} ?{r}))",
+                    key = sort_key,
+                    op = direction.operator(),
+                    k = batch_args.len() - 1,
+                    r = batch_args.len()
+                ));
+            }
+
+            let sql = format!(
+                "SELECT s.project_name, s.project_path, s.session_id, s.path,
+                        m.uuid, m.role, m.timestamp, {} AS rank, {} AS sort_key, m.rowid,
+                        m.text, m.tool_use, m.tool_result, m.thinking
+                 FROM {}
+                 JOIN sessions s ON s.path = m.session_path
+                 WHERE {}
+                 ORDER BY sort_key {dir}, m.rowid {dir}
+                 LIMIT {}",
+                rank,
+                sort_key,
+                from,
+                batch_clauses.join(" AND "),
+                SEARCH_BATCH,
+                dir = direction.keyword(),
+            );
+
+            let hits = self.read_hits(&sql, batch_args, kinds)?;
+            let exhausted = hits.len() < SEARCH_BATCH;
+            after = hits.last().map(|hit| hit.cursor.clone());
+
+            for hit in hits {
+                if !accept(&hit.blocks) {
+                    continue;
+                }
+                if !on_hit(hit) {
+                    return Ok(());
+                }
+            }
+
+            if exhausted {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Runs a search query, keeping the non-empty text of the searched
+    /// block kinds.
+    fn read_hits(
+        &self,
+        sql: &str,
+        args: Vec<Value>,
+        kinds: &[BlockKind],
+    ) -> Result<Vec<MessageHit>, String> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+        let mut rows = stmt
+            .query(params_from_iter(args))
+            .map_err(|e| e.to_string())?;
+
+        let mut hits = vec![];
+        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+            let mut blocks = vec![];
+            for (i, kind) in BlockKind::ALL.into_iter().enumerate() {
+                let text: String = row.get(10 + i).map_err(|e| e.to_string())?;
+                if kinds.contains(&kind) && !text.is_empty() {
+                    blocks.push((kind, text));
+                }
+            }
+
+            let key = match row.get::<_, Value>(8).map_err(|e| e.to_string())? {
+                Value::Real(rank) => rank.to_string(),
+                Value::Text(timestamp) => timestamp,
+                _ => String::new(),
+            };
+
+            hits.push(read_hit(row, blocks, key).map_err(|e| e.to_string())?);
+        }
+
+        Ok(hits)
+    }
+
+    /// Re-parses a session if its stamp differs from the cached row.
+    fn refresh(
+        &self,
+        source: &dyn SessionSource,
+        project: &Project,
+        session_path: &Path,
+    ) -> Result<(), String> {
+        let path = session_path.to_string_lossy().to_string();
+        let Some(SessionStamp { size, mtime_ms }) = source.stamp(session_path) else {
+            return Ok(());
+        };
+
+        let cached: Option<(u64, i64, String)> = self
+            .conn()
+            .query_row(
+                "SELECT size, mtime_ms, project_name FROM sessions WHERE path = ?1",
+                [&path],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        if cached == Some((size, mtime_ms, project.name.clone())) {
+            return Ok(());
+        }
+
+        // Parsed before the connection is locked, as reading a session can
+        // be slow and other commands wait on the lock.
+        let parsed = match source.records(session_path) {
+            Ok(records) => ParsedSession::from_records(records),
+            Err(_) => return Ok(()),
+        };
+        let title = source.session_title(session_path);
+
+        let session_id = session_path
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string();
+
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM sessions WHERE path = ?1", [&path])
+            .map_err(|e| e.to_string())?;
+        tx.execute(
+            "INSERT INTO sessions (path, source, project_name, project_path, session_id, title,
+                                   size, mtime_ms, input_tokens, output_tokens, message_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, 0, 0)",
+            params![
+                path,
+                source.id(),
+                project.name,
+                project.path,
+                session_id,
+                title,
+                size,
+                mtime_ms
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO messages (session_path, seq, uuid, role, timestamp, model, tools,
+                                           is_conversation, input_tokens, output_tokens,
+                                           text, tool_use, tool_result, thinking)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                )
+                .map_err(|e| e.to_string())?;
+
+            for (seq, row) in parsed.rows.iter().enumerate() {
+                let [text, tool_use, tool_result, thinking] = &row.blocks;
+                insert
+                    .execute(params![
+                        path,
+                        seq as i64,
+                        row.uuid,
+                        row.role,
+                        row.timestamp,
+                        row.model,
+                        row.tools,
+                        row.is_conversation,
+                        row.input_tokens,
+                        row.output_tokens,
+                        text,
+                        tool_use,
+                        tool_result,
+                        thinking,
+                    ])
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+
+        tx.execute(
+            "UPDATE sessions
+             SET input_tokens = ?2, output_tokens = ?3, reasoning_tokens = ?4,
+                 cache_read_tokens = ?5, cache_write_tokens = ?6, cost = ?7,
+                 message_count = ?8, first_timestamp = ?9, last_timestamp = ?10
+             WHERE path = ?1",
+            params![
+                path,
+                parsed.usage.input_tokens,
+                parsed.usage.output_tokens,
+                parsed.usage.reasoning_tokens,
+                parsed.usage.cache_read_tokens,
+                parsed.usage.cache_write_tokens,
+                parsed.usage.cost,
+                parsed.message_count,
+                parsed.first_timestamp,
+                parsed.last_timestamp
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+        tx.commit().map_err(|e| e.to_string())
+    }
+}
+
+/// A message as it is stored in the `messages` table.
+struct MessageRow {
+    uuid: Option<String>,
+    role: Option<String>,
+    timestamp: Option<String>,
+    model: Option<String>,
+    tools: String,
+    is_conversation: bool,
+    input_tokens: u64,
+    output_tokens: u64,
+    /// Text of each kind in `BlockKind::ALL` order.
+    blocks: [String; 4],
+}
+
+/// What the index stores about a session, read from its records.
+struct ParsedSession {
+    rows: Vec<MessageRow>,
+    usage: UsageTotals,
+    message_count: u32,
+    first_timestamp: Option<String>,
+    last_timestamp: Option<String>,
+}
+
+impl ParsedSession {
+    fn from_records(records: Records<'_>) -> Self {
+        let mut parsed = ParsedSession {
+            rows: vec![],
+            usage: UsageTotals::default(),
+            message_count: 0,
+            first_timestamp: None,
+            last_timestamp: None,
+        };
+
+        for msg in records {
+            let (input, output) = msg
+                .usage()
+                .map(|u| (u.input_tokens.unwrap_or(0), u.output_tokens.unwrap_or(0)))
+                .unwrap_or((0, 0));
+            if let Some(u) = msg.usage() {
+                parsed.usage.add(u);
+            }
+
+            if is_displayable(&msg) {
+                parsed.message_count += 1;
+            }
+
+            if msg.is_conversation() {
+                if let Some(ts) = msg.timestamp.as_deref() {
+                    if chrono::DateTime::parse_from_rfc3339(ts).is_ok() {
+                        if parsed.first_timestamp.is_none() {
+                            parsed.first_timestamp = Some(ts.to_string());
+                        }
+                        parsed.last_timestamp = Some(ts.to_string());
+                    }
+                }
+            }
+
+            parsed.rows.push(MessageRow {
+                role: msg.role().map(String::from),
+                model: msg.model().map(String::from),
+                tools: format!(",{},", msg.tool_names().join(",")),
+                is_conversation: msg.is_conversation(),
+                input_tokens: input,
+                output_tokens: output,
+                blocks: BlockKind::ALL.map(|kind| extract_block_text(&msg, kind)),
+                uuid: msg.uuid,
+                timestamp: msg.timestamp,
+            });
+        }
+
+        parsed
+    }
+}
+
+fn read_hit(
+    row: &rusqlite::Row,
+    blocks: Vec<BlockText>,
+    key: String,
+) -> rusqlite::Result<MessageHit> {
+    Ok(MessageHit {
+        project_name: row.get(0)?,
+        project_path: row.get(1)?,
+        session_id: row.get(2)?,
+        session_path: row.get(3)?,
+        uuid: row.get(4)?,
+        role: row.get(5)?,
+        timestamp: row.get(6)?,
+        blocks,
+        score: -row.get::<_, f64>(7)?,
+        cursor: SearchCursor {
+            key,
+            rowid: row.get(9)?,
+        },
+    })
+}
+
+/// Default location of the on-disk cache.
+pub fn default_index_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("agent-log").join("index.sqlite3"))
+}
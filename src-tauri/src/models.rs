@@ -0,0 +1,332 @@
+use crate::record::{content_blocks, ContentBlock};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Project {
+    pub name: String,
+    pub path: String,
+    pub session_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub path: String,
+    pub size: u64,
+    pub modified: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// Cost in USD the agent recorded, when it records one.
+    pub cost: Option<f64>,
+}
+
+/// Token counts of one message, under the names Claude uses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<u64>,
+    /// Reported separately by agents other than Claude Code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<u64>,
+    /// Cost in USD, for agents that record it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+/// [`TokenUsage`] summed over many messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// `None` until a message with a recorded cost is added.
+    pub cost: Option<f64>,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, usage: &TokenUsage) {
+        self.input_tokens += usage.input_tokens.unwrap_or(0);
+        self.output_tokens += usage.output_tokens.unwrap_or(0);
+        self.reasoning_tokens += usage.reasoning_tokens.unwrap_or(0);
+        self.cache_read_tokens += usage.cache_read_input_tokens.unwrap_or(0);
+        self.cache_write_tokens += usage.cache_creation_input_tokens.unwrap_or(0);
+        if let Some(cost) = usage.cost {
+            self.cost = Some(self.cost.unwrap_or(0.0) + cost);
+        }
+    }
+}
+
+/// Kind of content block, as far as search is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    Text,
+    /// The tool name and input of a `tool_use` block.
+    ToolUse,
+    /// The output returned in a `tool_result` block.
+    ToolResult,
+    Thinking,
+}
+
+impl BlockKind {
+    pub const ALL: [BlockKind; 4] = [
+        BlockKind::Text,
+        BlockKind::ToolUse,
+        BlockKind::ToolResult,
+        BlockKind::Thinking,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BlockKind::Text => "text",
+            BlockKind::ToolUse => "tool_use",
+            BlockKind::ToolResult => "tool_result",
+            BlockKind::Thinking => "thinking",
+        }
+    }
+}
+
+impl std::str::FromStr for BlockKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BlockKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown block kind: {} (expected text, tool_use, tool_result or thinking)",
+                    s
+                )
+            })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Message {
+    #[serde(rename = "type")]
+    pub msg_type: Option<String>,
+    pub uuid: Option<String>,
+    #[serde(rename = "parentUuid")]
+    pub parent_uuid: Option<String>,
+    pub timestamp: Option<String>,
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+    /// Set on records written by a subagent spawned through the `Task` tool.
+    #[serde(rename = "isSidechain", default, skip_serializing_if = "is_false")]
+    pub is_sidechain: bool,
+    pub message: Option<MessageContent>,
+    /// The tools this message called, each with the result it got back.
+    /// Filled in when loading a session, never read from disk.
+    #[serde(rename = "toolCalls", default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// `id` of the `tool_use` block.
+    pub id: String,
+    pub name: String,
+    pub input: serde_json::Value,
+    /// Text of the matching `tool_result`; `None` when none was written,
+    /// e.g. because the call was interrupted.
+    pub output: Option<String>,
+    pub is_error: bool,
+    /// Time from the call to its result.
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageContent {
+    pub role: Option<String>,
+    pub content: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+}
+
+impl Message {
+    pub fn is_conversation(&self) -> bool {
+        matches!(self.msg_type.as_deref(), Some("user") | Some("assistant"))
+    }
+
+    pub fn role(&self) -> Option<&str> {
+        self.message.as_ref().and_then(|m| m.role.as_deref())
+    }
+
+    pub fn usage(&self) -> Option<&TokenUsage> {
+        self.message.as_ref().and_then(|m| m.usage.as_ref())
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        self.message.as_ref().and_then(|m| m.model.as_deref())
+    }
+
+    /// The content as typed blocks; empty when there is none.
+    pub fn blocks(&self) -> Vec<ContentBlock> {
+        self.message
+            .as_ref()
+            .and_then(|m| m.content.as_ref())
+            .map(content_blocks)
+            .unwrap_or_default()
+    }
+
+    /// Names of the tools this message calls, through `tool_use` blocks or
+    /// the tool calls a source read in directly.
+    pub fn tool_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .message
+            .as_ref()
+            .and_then(|m| m.content.as_ref())
+            .and_then(|c| c.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+                    .filter_map(|item| item.get("name").and_then(|n| n.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        names.extend(self.tool_calls.iter().map(|call| call.name.as_str()));
+        names
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+pub fn has_text_content(message: &Option<MessageContent>) -> bool {
+    let Some(msg) = message else {
+        return false;
+    };
+    let Some(content) = &msg.content else {
+        return false;
+    };
+
+    match content {
+        serde_json::Value::String(s) => !s.trim().is_empty(),
+        serde_json::Value::Array(arr) => arr.iter().any(|item| {
+            if item.get("type").and_then(|t| t.as_str()) == Some("text") {
+                item.get("text")
+                    .and_then(|t| t.as_str())
+                    .map(|s| !s.trim().is_empty())
+                    .unwrap_or(false)
+            } else {
+                false
+            }
+        }),
+        _ => false,
+    }
+}
+
+pub fn extract_text_content(message: &Option<MessageContent>) -> String {
+    let Some(msg) = message else {
+        return String::new();
+    };
+    let Some(content) = &msg.content else {
+        return String::new();
+    };
+
+    match content {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(arr) => arr
+            .iter()
+            .filter_map(|item| {
+                if item.get("type")?.as_str()? == "text" {
+                    item.get("text")?.as_str().map(String::from)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Searchable text of every block of one kind, one block per line group.
+///
+/// Sources that record tool calls outside the content blocks, such as
+/// OpenCode and Gemini, have their `tool_calls` searched instead.
+pub fn extract_block_text(msg: &Message, kind: BlockKind) -> String {
+    if kind == BlockKind::Text {
+        return extract_text_content(&msg.message);
+    }
+
+    let items = msg
+        .message
+        .as_ref()
+        .and_then(|m| m.content.as_ref())
+        .and_then(|c| c.as_array());
+
+    let text = items
+        .into_iter()
+        .flatten()
+        .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some(kind.as_str()))
+        .filter_map(|item| match kind {
+            BlockKind::ToolUse => {
+                let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("");
+                let input = item.get("input").map(value_text).unwrap_or_default();
+                Some(format!("{}\n{}", name, input))
+            }
+            BlockKind::ToolResult => item.get("content").map(value_text),
+            BlockKind::Thinking => item.get("thinking")?.as_str().map(String::from),
+            BlockKind::Text => None,
+        })
+        .filter(|text| !text.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    if !text.is_empty() {
+        return text;
+    }
+
+    msg.tool_calls
+        .iter()
+        .filter_map(|call| match kind {
+            BlockKind::ToolUse => Some(format!("{}\n{}", call.name, value_text(&call.input))),
+            BlockKind::ToolResult => call.output.clone(),
+            BlockKind::Text | BlockKind::Thinking => None,
+        })
+        .filter(|text| !text.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Flattens tool input or output into plain text: strings as they are, text
+/// blocks by their text, images not at all, and other values field by field.
+pub(crate) fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter(|item| item.get("type").and_then(|t| t.as_str()) != Some("image"))
+            .map(|item| match item.get("text").and_then(|t| t.as_str()) {
+                Some(text) => text.to_string(),
+                None => value_text(item),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        serde_json::Value::Object(fields) => fields
+            .values()
+            .map(value_text)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,410 @@
+use crate::models::BlockKind;
+use crate::search::SearchError;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How the text part of a query is matched against messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchMode {
+    /// Stemmed word matching through the FTS index, ranked by relevance.
+    #[default]
+    FullText,
+    /// Case-insensitive substring.
+    Substring,
+    /// Exact substring, e.g. `HashMap<String`.
+    CaseSensitive,
+    /// The whole text part is one regular expression.
+    Regex,
+    /// Case-insensitive match of whole words only.
+    WholeWord,
+}
+
+/// Parsed form of the search box input, e.g.
+/// `role:assistant project:foo after:2026-01-01 tool:Bash "exact phrase" -excluded`.
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    pub mode: SearchMode,
+    /// Text terms that must all match.
+    pub terms: Vec<Term>,
+    /// Text terms written as `-term`; messages matching any of them are dropped.
+    pub excluded: Vec<Term>,
+    /// In regex mode, the input with its filters removed. Terms stay empty.
+    pub pattern: Option<String>,
+    pub filters: Vec<Filter>,
+    /// Content blocks the text is matched against; text blocks only when empty.
+    pub blocks: Vec<BlockKind>,
+}
+
+/// One full-text term.
+#[derive(Debug)]
+pub enum Term {
+    /// A bare word; `prefix` is set when it was written as `word*`.
+    Word { text: String, prefix: bool },
+    /// A `"quoted phrase"` whose words must appear next to each other.
+    Phrase(String),
+}
+
+/// A `key:value` restriction on message metadata.
+#[derive(Debug)]
+pub enum Filter {
+    /// `role:user` or `role:assistant`.
+    Role(String),
+    /// `project:foo`, matched against the project name and path.
+    Project(String),
+    /// `after:YYYY-MM-DD`, inclusive.
+    After(NaiveDate),
+    /// `before:YYYY-MM-DD`, exclusive.
+    Before(NaiveDate),
+    /// `tool:Bash`, messages that called the tool.
+    Tool(String),
+    /// `model:opus`, matched against the model ID.
+    Model(String),
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str, mode: SearchMode) -> Result<Self, SearchError> {
+        if mode == SearchMode::Regex {
+            return Self::parse_pattern(input);
+        }
+
+        let mut query = SearchQuery {
+            mode,
+            ..Default::default()
+        };
+        let mut chars = input.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+
+            let negated = c == '-';
+            if negated {
+                chars.next();
+            }
+
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+                if !phrase.trim().is_empty() {
+                    query.push_term(Term::Phrase(phrase.trim().to_string()), negated);
+                }
+                continue;
+            }
+
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+
+            if !negated {
+                if let Some(filter) = parse_filter(&word)? {
+                    query.filters.push(filter);
+                    continue;
+                }
+            }
+
+            let prefix = word.len() > 1 && word.ends_with('*');
+            let text = word.trim_end_matches('*').to_string();
+            if !text.is_empty() {
+                query.push_term(Term::Word { text, prefix }, negated);
+            }
+        }
+
+        Ok(query)
+    }
+
+    /// Regex mode keeps the input verbatim, since spaces, quotes and `-` are
+    /// part of the pattern. Only recognized `key:value` words are taken out.
+    fn parse_pattern(input: &str) -> Result<Self, SearchError> {
+        let mut query = SearchQuery {
+            mode: SearchMode::Regex,
+            ..Default::default()
+        };
+        let mut pattern = String::new();
+        let mut rest = input;
+
+        while !rest.is_empty() {
+            let word_start = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len());
+            let word_end = rest[word_start..]
+                .find(char::is_whitespace)
+                .map_or(rest.len(), |i| word_start + i);
+            let after_space = rest[word_end..]
+                .find(|c: char| !c.is_whitespace())
+                .map_or(rest.len(), |i| word_end + i);
+
+            match parse_filter(&rest[word_start..word_end])? {
+                Some(filter) => {
+                    pattern.push_str(&rest[..word_start]);
+                    query.filters.push(filter);
+                }
+                None => pattern.push_str(&rest[..after_space]),
+            }
+            rest = &rest[after_space..];
+        }
+
+        let pattern = pattern.trim();
+        if !pattern.is_empty() {
+            query.pattern = Some(pattern.to_string());
+        }
+
+        Ok(query)
+    }
+
+    pub fn searched_blocks(&self) -> &[BlockKind] {
+        if self.blocks.is_empty() {
+            &[BlockKind::Text]
+        } else {
+            &self.blocks
+        }
+    }
+
+    /// A query of only `-term`s is not empty: it matches every message
+    /// without them.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+            && self.excluded.is_empty()
+            && self.pattern.is_none()
+            && self.filters.is_empty()
+    }
+
+    /// FTS5 `MATCH` expression requiring every positive term.
+    pub fn fts_match(&self) -> Option<String> {
+        to_fts_expr(&self.terms, " ")
+    }
+
+    /// FTS5 `MATCH` expression matching any excluded term.
+    pub fn fts_excluded(&self) -> Option<String> {
+        to_fts_expr(&self.excluded, " OR ")
+    }
+
+    fn push_term(&mut self, term: Term, negated: bool) {
+        if negated {
+            self.excluded.push(term);
+        } else {
+            self.terms.push(term);
+        }
+    }
+}
+
+impl FromStr for SearchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full-text" => Ok(SearchMode::FullText),
+            "substring" => Ok(SearchMode::Substring),
+            "case-sensitive" => Ok(SearchMode::CaseSensitive),
+            "regex" => Ok(SearchMode::Regex),
+            "whole-word" => Ok(SearchMode::WholeWord),
+            _ => Err(format!(
+                "Unknown search mode: {} (expected full-text, substring, case-sensitive, regex or whole-word)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SearchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SearchMode::FullText => "full-text",
+            SearchMode::Substring => "substring",
+            SearchMode::CaseSensitive => "case-sensitive",
+            SearchMode::Regex => "regex",
+            SearchMode::WholeWord => "whole-word",
+        })
+    }
+}
+
+impl Term {
+    pub fn text(&self) -> &str {
+        match self {
+            Term::Word { text, .. } | Term::Phrase(text) => text,
+        }
+    }
+}
+
+/// Recognizes `key:value` filters. Words with an unknown key, such as URLs,
+/// stay plain text.
+fn parse_filter(word: &str) -> Result<Option<Filter>, SearchError> {
+    let Some((key, value)) = word.split_once(':') else {
+        return Ok(None);
+    };
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let filter = match key.to_lowercase().as_str() {
+        "role" => Filter::Role(value.to_lowercase()),
+        "project" => Filter::Project(value.to_string()),
+        "after" => Filter::After(parse_date(key, value)?),
+        "before" => Filter::Before(parse_date(key, value)?),
+        "tool" => Filter::Tool(value.to_string()),
+        "model" => Filter::Model(value.to_string()),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(filter))
+}
+
+fn parse_date(key: &str, value: &str) -> Result<NaiveDate, SearchError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| SearchError::InvalidFilter {
+        message: format!("Invalid date for {}: {} (expected YYYY-MM-DD)", key, value),
+    })
+}
+
+/// Every term is quoted so punctuation in the user's input is never parsed as
+/// FTS5 syntax.
+fn to_fts_expr(terms: &[Term], separator: &str) -> Option<String> {
+    if terms.is_empty() {
+        return None;
+    }
+
+    Some(
+        terms
+            .iter()
+            .map(|term| {
+                let quoted = format!("\"{}\"", term.text().replace('"', "\"\""));
+                match term {
+                    Term::Word { prefix: true, .. } => format!("{}*", quoted),
+                    _ => quoted,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(separator),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> SearchQuery {
+        SearchQuery::parse(input, SearchMode::FullText).unwrap()
+    }
+
+    fn texts(terms: &[Term]) -> Vec<&str> {
+        terms.iter().map(Term::text).collect()
+    }
+
+    #[test]
+    fn quoted_phrases_keep_their_spaces() {
+        let query = parse(r#"fix "exact  phrase" -"not this" "unclosed"#);
+
+        assert!(matches!(&query.terms[1], Term::Phrase(p) if p == "exact  phrase"));
+        assert_eq!(texts(&query.terms), ["fix", "exact  phrase", "unclosed"]);
+        assert_eq!(texts(&query.excluded), ["not this"]);
+    }
+
+    #[test]
+    fn dashed_words_are_excluded() {
+        let query = parse("panic -test -role:user");
+
+        assert_eq!(texts(&query.terms), ["panic"]);
+        assert_eq!(texts(&query.excluded), ["test", "role:user"]);
+        assert!(query.filters.is_empty());
+    }
+
+    #[test]
+    fn exclusion_only_query_is_not_empty() {
+        assert!(!parse("-foo").is_empty());
+        assert!(parse("  ").is_empty());
+        assert!(parse("\"\" *").is_empty());
+    }
+
+    #[test]
+    fn prefix_words() {
+        let query = parse("hash* *");
+
+        assert!(matches!(&query.terms[..], [Term::Word { text, prefix: true }] if text == "hash"));
+    }
+
+    #[test]
+    fn filters_are_taken_out_of_the_text() {
+        let query = parse(
+            "role:Assistant project:agent-log after:2026-01-01 before:2026-02-01 \
+             tool:Bash model:opus https://example.com",
+        );
+
+        assert_eq!(texts(&query.terms), ["https://example.com"]);
+        match &query.filters[..] {
+            [Filter::Role(role), Filter::Project(project), Filter::After(after), Filter::Before(before), Filter::Tool(tool), Filter::Model(model)] =>
+            {
+                assert_eq!(role, "assistant");
+                assert_eq!(project, "agent-log");
+                assert_eq!(*after, NaiveDate::from_ymd_opt(2026, 1, 1).unwrap());
+                assert_eq!(*before, NaiveDate::from_ymd_opt(2026, 2, 1).unwrap());
+                assert_eq!(tool, "Bash");
+                assert_eq!(model, "opus");
+            }
+            filters => panic!("unexpected filters: {:?}", filters),
+        }
+    }
+
+    #[test]
+    fn filter_without_value_is_text() {
+        let query = parse("role:");
+
+        assert_eq!(texts(&query.terms), ["role:"]);
+        assert!(query.filters.is_empty());
+    }
+
+    #[test]
+    fn malformed_dates_are_rejected() {
+        for input in ["after:2026-13-01", "before:yesterday", "after:2026/01/01"] {
+            assert!(
+                matches!(
+                    SearchQuery::parse(input, SearchMode::FullText),
+                    Err(SearchError::InvalidFilter { .. })
+                ),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn regex_mode_keeps_the_pattern_verbatim() {
+        let query = SearchQuery::parse(r#"fn \w+\(  -"x" role:user"#, SearchMode::Regex).unwrap();
+
+        assert_eq!(query.pattern.as_deref(), Some(r#"fn \w+\(  -"x""#));
+        assert!(query.terms.is_empty() && query.excluded.is_empty());
+        assert!(matches!(&query.filters[..], [Filter::Role(role)] if role == "user"));
+    }
+
+    #[test]
+    fn fts_expressions_quote_every_term() {
+        let query = parse(r#"say* "he said" NOT (a) -x -OR"#);
+
+        assert_eq!(
+            query.fts_match().as_deref(),
+            Some(r#""say"* "he said" "NOT" "(a)""#)
+        );
+        assert_eq!(query.fts_excluded().as_deref(), Some(r#""x" OR "OR""#));
+        assert_eq!(parse("-x").fts_match(), None);
+    }
+
+    #[test]
+    fn fts_expressions_escape_quotes() {
+        let query = SearchQuery {
+            terms: vec![Term::Word {
+                text: r#"a"b"#.to_string(),
+                prefix: true,
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(query.fts_match().as_deref(), Some(r#""a""b"*"#));
+    }
+}
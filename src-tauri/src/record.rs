@@ -0,0 +1,243 @@
+use crate::models::{BlockKind, Message, TokenUsage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// One line of a Claude session file, by the kind of record it holds.
+///
+/// [`Message`] only models the conversation; this also keeps the summaries,
+/// system notices and snapshots written alongside it. Records of a kind this
+/// does not know, or that fail to parse as their kind, are kept as raw JSON.
+/// Mirrored by `SessionRecord` in `src/types/index.ts`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Record {
+    User(ConversationRecord),
+    Assistant(ConversationRecord),
+    /// Title Claude Code generates for the conversation ending at `leafUuid`.
+    Summary(SummaryRecord),
+    System(SystemRecord),
+    /// Marks where the conversation was compacted into a summary.
+    CompactBoundary(CompactBoundary),
+    /// Backups of the files edited up to a message, used to rewind them.
+    FileHistorySnapshot(FileHistorySnapshot),
+    Unknown {
+        raw: Value,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationRecord {
+    pub uuid: Option<String>,
+    pub parent_uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub session_id: Option<String>,
+    pub is_sidechain: bool,
+    pub model: Option<String>,
+    pub usage: Option<TokenUsage>,
+    pub content: Vec<ContentBlock>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummaryRecord {
+    pub summary: String,
+    pub leaf_uuid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemRecord {
+    pub uuid: Option<String>,
+    pub parent_uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub subtype: Option<String>,
+    /// `info`, `warning` or `error`.
+    pub level: Option<String>,
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactBoundary {
+    pub uuid: Option<String>,
+    /// Last message before the boundary; `parentUuid` is unset on these.
+    pub logical_parent_uuid: Option<String>,
+    pub timestamp: Option<String>,
+    pub content: Option<String>,
+    #[serde(rename = "compactMetadata")]
+    pub metadata: Option<CompactMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactMetadata {
+    /// `auto` or `manual`.
+    pub trigger: Option<String>,
+    /// Context size in tokens before compacting.
+    pub pre_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHistorySnapshot {
+    pub message_id: Option<String>,
+    #[serde(default)]
+    pub is_snapshot_update: bool,
+    pub snapshot: Option<Snapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    pub timestamp: Option<String>,
+    /// Backup details by edited file path.
+    #[serde(default)]
+    pub tracked_file_backups: BTreeMap<String, Value>,
+}
+
+/// One item of a message's `content` array.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        thinking: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        #[serde(default)]
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        /// A string, or text and image blocks.
+        #[serde(default)]
+        content: Value,
+        #[serde(default)]
+        is_error: bool,
+    },
+    Image {
+        source: Value,
+    },
+    #[serde(skip_deserializing)]
+    Unknown {
+        raw: Value,
+    },
+}
+
+impl Record {
+    pub fn from_value(value: Value) -> Self {
+        let subtype = value.get("subtype").and_then(|s| s.as_str());
+
+        let parsed = match value.get("type").and_then(|t| t.as_str()) {
+            Some("user") | Some("assistant") => Message::deserialize(&value).map(Record::from),
+            Some("summary") => SummaryRecord::deserialize(&value).map(Record::Summary),
+            Some("system") if subtype == Some("compact_boundary") => {
+                CompactBoundary::deserialize(&value).map(Record::CompactBoundary)
+            }
+            Some("system") => SystemRecord::deserialize(&value).map(Record::System),
+            Some("file-history-snapshot") => {
+                FileHistorySnapshot::deserialize(&value).map(Record::FileHistorySnapshot)
+            }
+            _ => return Record::Unknown { raw: value },
+        };
+
+        parsed.unwrap_or(Record::Unknown { raw: value })
+    }
+
+    /// Parses one JSONL line; `None` when it is not JSON at all.
+    pub fn parse(line: &str) -> Option<Self> {
+        serde_json::from_str(line).ok().map(Self::from_value)
+    }
+
+    pub fn is_sidechain(&self) -> bool {
+        matches!(self, Record::User(r) | Record::Assistant(r) if r.is_sidechain)
+    }
+
+    pub fn timestamp(&self) -> Option<&str> {
+        match self {
+            Record::User(r) | Record::Assistant(r) => r.timestamp.as_deref(),
+            Record::System(r) => r.timestamp.as_deref(),
+            Record::CompactBoundary(r) => r.timestamp.as_deref(),
+            Record::FileHistorySnapshot(r) => r.snapshot.as_ref()?.timestamp.as_deref(),
+            Record::Summary(_) => None,
+            Record::Unknown { raw } => raw.get("timestamp")?.as_str(),
+        }
+    }
+}
+
+impl From<Message> for Record {
+    fn from(msg: Message) -> Self {
+        let is_assistant = match msg.msg_type.as_deref() {
+            Some("user") => false,
+            Some("assistant") => true,
+            _ => {
+                return Record::Unknown {
+                    raw: serde_json::to_value(&msg).unwrap_or_default(),
+                }
+            }
+        };
+
+        let (model, usage, content) = match msg.message {
+            Some(message) => (
+                message.model,
+                message.usage,
+                message
+                    .content
+                    .as_ref()
+                    .map(content_blocks)
+                    .unwrap_or_default(),
+            ),
+            None => (None, None, vec![]),
+        };
+
+        let record = ConversationRecord {
+            uuid: msg.uuid,
+            parent_uuid: msg.parent_uuid,
+            timestamp: msg.timestamp,
+            session_id: msg.session_id,
+            is_sidechain: msg.is_sidechain,
+            model,
+            usage,
+            content,
+        };
+
+        if is_assistant {
+            Record::Assistant(record)
+        } else {
+            Record::User(record)
+        }
+    }
+}
+
+impl ContentBlock {
+    pub fn from_value(value: &Value) -> Self {
+        Self::deserialize(value).unwrap_or_else(|_| ContentBlock::Unknown { raw: value.clone() })
+    }
+
+    /// The kind search indexes this block as, if any.
+    pub fn kind(&self) -> Option<BlockKind> {
+        match self {
+            ContentBlock::Text { .. } => Some(BlockKind::Text),
+            ContentBlock::Thinking { .. } => Some(BlockKind::Thinking),
+            ContentBlock::ToolUse { .. } => Some(BlockKind::ToolUse),
+            ContentBlock::ToolResult { .. } => Some(BlockKind::ToolResult),
+            ContentBlock::Image { .. } | ContentBlock::Unknown { .. } => None,
+        }
+    }
+}
+
+/// Message content as blocks. Plain string content becomes a single text block.
+pub fn content_blocks(content: &Value) -> Vec<ContentBlock> {
+    match content {
+        Value::String(text) => vec![ContentBlock::Text { text: text.clone() }],
+        Value::Array(items) => items.iter().map(ContentBlock::from_value).collect(),
+        Value::Null => vec![],
+        other => vec![ContentBlock::Unknown { raw: other.clone() }],
+    }
+}
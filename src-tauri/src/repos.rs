@@ -0,0 +1,258 @@
+use crate::index::SessionIndex;
+use crate::models::Session;
+use crate::sources::{project_display_name, SourceRegistry};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long a directory's git location is reused before git is asked again.
+const GIT_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Sessions and tokens summed over a group.
+#[derive(Debug, Default, Serialize)]
+pub struct GroupTotals {
+    pub session_count: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// Recorded cost in USD; `None` when no session recorded one.
+    pub cost: Option<f64>,
+}
+
+impl GroupTotals {
+    fn add(&mut self, session: &Session) {
+        self.session_count += 1;
+        self.input_tokens += session.input_tokens;
+        self.output_tokens += session.output_tokens;
+        self.reasoning_tokens += session.reasoning_tokens;
+        self.cache_read_tokens += session.cache_read_tokens;
+        self.cache_write_tokens += session.cache_write_tokens;
+        if let Some(cost) = session.cost {
+            *self.cost.get_or_insert(0.0) += cost;
+        }
+    }
+}
+
+/// Sessions of one git repository, across its worktrees and the
+/// subdirectories the agents were started in. Sessions outside git, or whose
+/// directory is gone, get a group of their own per directory.
+#[derive(Debug, Serialize)]
+pub struct RepoGroup {
+    pub name: String,
+    /// URL of the `origin` remote.
+    pub remote: Option<String>,
+    /// The main worktree; `None` outside git.
+    pub root: Option<String>,
+    #[serde(flatten)]
+    pub totals: GroupTotals,
+    pub workdirs: Vec<WorkdirGroup>,
+}
+
+/// Sessions started in the same directory.
+#[derive(Debug, Serialize)]
+pub struct WorkdirGroup {
+    pub name: String,
+    /// `None` for sessions whose source does not record where they ran.
+    pub path: Option<String>,
+    /// Toplevel of the worktree the directory is in.
+    pub worktree: Option<String>,
+    #[serde(flatten)]
+    pub totals: GroupTotals,
+    /// Newest first.
+    pub sessions: Vec<Session>,
+}
+
+/// Where a directory is in git.
+#[derive(Clone)]
+struct GitLocation {
+    /// The main worktree, or the git directory of a bare repository.
+    root: PathBuf,
+    worktree: PathBuf,
+    remote: Option<String>,
+}
+
+/// Every session of every project, grouped by repository and then by the
+/// directory it ran in.
+pub fn repo_groups(
+    registry: &SourceRegistry,
+    index: &SessionIndex,
+) -> Result<Vec<RepoGroup>, String> {
+    // Keyed by remote, or by root when there is none, then by directory.
+    let mut repos: BTreeMap<String, RepoGroup> = BTreeMap::new();
+
+    for source in registry.iter() {
+        for project in source.list_projects()? {
+            let sessions = index.list_sessions(source, &project)?;
+
+            for session in sessions {
+                let dir = source.working_dir(Path::new(&session.path));
+                let location = dir.as_deref().and_then(git_location);
+                let location = location.as_ref();
+
+                let repo_key = match (location, &dir) {
+                    (Some(git), _) => git
+                        .remote
+                        .clone()
+                        .unwrap_or_else(|| git.root.to_string_lossy().to_string()),
+                    (None, Some(dir)) => dir.to_string_lossy().to_string(),
+                    (None, None) => project.path.clone(),
+                };
+
+                let repo = repos.entry(repo_key).or_insert_with(|| RepoGroup {
+                    name: match (location, &dir) {
+                        (Some(git), _) => project_display_name(&git.root),
+                        (None, Some(dir)) => project_display_name(dir),
+                        (None, None) => project.name.clone(),
+                    },
+                    remote: location.and_then(|git| git.remote.clone()),
+                    root: location.map(|git| git.root.to_string_lossy().to_string()),
+                    totals: GroupTotals::default(),
+                    workdirs: vec![],
+                });
+
+                let path = dir.as_ref().map(|d| d.to_string_lossy().to_string());
+                let workdir = match repo.workdirs.iter().position(|w| w.path == path) {
+                    Some(i) => &mut repo.workdirs[i],
+                    None => {
+                        repo.workdirs.push(WorkdirGroup {
+                            name: match &dir {
+                                Some(dir) => project_display_name(dir),
+                                None => project.name.clone(),
+                            },
+                            path,
+                            worktree: location
+                                .map(|git| git.worktree.to_string_lossy().to_string()),
+                            totals: GroupTotals::default(),
+                            sessions: vec![],
+                        });
+                        repo.workdirs.last_mut().expect("just pushed")
+                    }
+                };
+
+                repo.totals.add(&session);
+                workdir.totals.add(&session);
+                workdir.sessions.push(session);
+            }
+        }
+    }
+
+    let mut groups: Vec<RepoGroup> = repos.into_values().collect();
+    for repo in &mut groups {
+        repo.workdirs.sort_by(|a, b| a.name.cmp(&b.name));
+        for workdir in &mut repo.workdirs {
+            workdir
+                .sessions
+                .sort_by_key(|s| std::cmp::Reverse(s.modified));
+        }
+    }
+    groups.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(groups)
+}
+
+/// Git locations of directories, and `origin` remotes of repositories, with
+/// when they were looked up. Shared across calls, as every lookup runs git.
+#[derive(Default)]
+struct GitCache {
+    locations: HashMap<PathBuf, (Instant, Option<GitLocation>)>,
+    remotes: HashMap<PathBuf, (Instant, Option<String>)>,
+}
+
+fn git_cache() -> &'static Mutex<GitCache> {
+    static CACHE: OnceLock<Mutex<GitCache>> = OnceLock::new();
+    CACHE.get_or_init(Mutex::default)
+}
+
+/// The repository and worktree `dir` is in, or `None` when it is not in one
+/// or no longer exists.
+fn git_location(dir: &Path) -> Option<GitLocation> {
+    let now = Instant::now();
+    let fresh = |looked_up: &Instant| now.duration_since(*looked_up) < GIT_CACHE_TTL;
+
+    if let Some((looked_up, location)) = git_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .locations
+        .get(dir)
+    {
+        if fresh(looked_up) {
+            return location.clone();
+        }
+    }
+
+    let location = find_git_location(dir);
+    git_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .locations
+        .insert(dir.to_path_buf(), (now, location.clone()));
+    location
+}
+
+fn find_git_location(dir: &Path) -> Option<GitLocation> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel", "--git-common-dir"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let worktree = PathBuf::from(lines.next()?);
+    // Relative to `dir` unless it is outside it.
+    let common_dir = dir.join(lines.next()?);
+    let common_dir = common_dir.canonicalize().unwrap_or(common_dir);
+
+    let remote = origin_remote(&common_dir);
+    let root = match common_dir.file_name() {
+        Some(name) if name == ".git" => common_dir.parent()?.to_path_buf(),
+        _ => common_dir,
+    };
+
+    Some(GitLocation {
+        root,
+        worktree,
+        remote,
+    })
+}
+
+/// URL of the `origin` remote of the repository whose git directory is
+/// `common_dir`, looked up once for all of its worktrees and subdirectories.
+fn origin_remote(common_dir: &Path) -> Option<String> {
+    if let Some((looked_up, remote)) = git_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remotes
+        .get(common_dir)
+    {
+        if looked_up.elapsed() < GIT_CACHE_TTL {
+            return remote.clone();
+        }
+    }
+
+    let remote = Command::new("git")
+        .arg("--git-dir")
+        .arg(common_dir)
+        .args(["config", "--get", "remote.origin.url"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|remote| !remote.is_empty());
+
+    git_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remotes
+        .insert(common_dir.to_path_buf(), (Instant::now(), remote.clone()));
+    remote
+}
@@ -0,0 +1,418 @@
+use crate::index::{BlockText, SearchCursor, SessionIndex};
+use crate::models::BlockKind;
+use crate::query::{SearchMode, SearchQuery, Term};
+use crate::sources::SourceRegistry;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Hits per page when the caller does not ask for a size.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Hits sent per streamed batch.
+const BATCH_SIZE: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchRequest {
+    pub query: String,
+    #[serde(default)]
+    pub mode: SearchMode,
+    /// `next_cursor` of the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default = "default_page_size")]
+    pub limit: usize,
+    /// Block kinds to search in; only text blocks when empty.
+    #[serde(default)]
+    pub blocks: Vec<BlockKind>,
+}
+
+/// One page of results and where the next one starts, if there is one.
+#[derive(Debug, Serialize)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    pub next_cursor: Option<String>,
+}
+
+/// Messages sent over the channel of a streaming search.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "event", content = "data")]
+pub enum SearchEvent {
+    /// The next batch of hits, in result order.
+    Results { results: Vec<SearchResult> },
+    /// The page is complete.
+    Finished { next_cursor: Option<String> },
+    /// A newer search started before this one finished.
+    Cancelled,
+}
+
+/// Counts started searches, so a streaming search can notice that a newer
+/// one replaced it and stop.
+#[derive(Default)]
+pub struct SearchCancellation {
+    generation: AtomicU64,
+}
+
+impl SearchCancellation {
+    /// Cancels any running search and returns the new search's generation.
+    pub fn begin(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn is_current(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub project_name: String,
+    pub project_path: String,
+    pub session_id: String,
+    pub session_path: String,
+    pub message_uuid: String,
+    pub role: String,
+    /// Kind of the block the preview was taken from.
+    pub block: BlockKind,
+    pub content_preview: String,
+    /// Matched parts of `content_preview`, as character offsets.
+    pub highlights: Vec<MatchSpan>,
+    pub timestamp: String,
+    pub score: f64,
+}
+
+/// Half-open `[start, end)` range of characters.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MatchSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Why a search could not run. Serialized with a `kind` tag so the frontend
+/// can tell a bad pattern from a broken index.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SearchError {
+    /// A `key:value` filter with a malformed value.
+    InvalidFilter { message: String },
+    /// A regex mode pattern that does not compile.
+    InvalidRegex { pattern: String, message: String },
+    /// Reading the sessions or querying the index failed.
+    Index { message: String },
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::InvalidFilter { message } | SearchError::Index { message } => {
+                f.write_str(message)
+            }
+            SearchError::InvalidRegex { pattern, message } => {
+                write!(f, "Invalid regex {:?}: {}", pattern, message)
+            }
+        }
+    }
+}
+
+impl From<String> for SearchError {
+    fn from(message: String) -> Self {
+        SearchError::Index { message }
+    }
+}
+
+/// Decides which messages match in the modes that bypass the FTS index, and
+/// finds the spans to highlight in every mode.
+pub struct Matcher {
+    required: Vec<Regex>,
+    excluded: Vec<Regex>,
+    highlight: Option<Regex>,
+}
+
+impl Matcher {
+    pub fn new(query: &SearchQuery) -> Result<Self, SearchError> {
+        if let Some(pattern) = &query.pattern {
+            let regex = RegexBuilder::new(pattern)
+                .size_limit(1 << 20)
+                .build()
+                .map_err(|e| SearchError::InvalidRegex {
+                    pattern: pattern.clone(),
+                    message: e.to_string(),
+                })?;
+            return Ok(Self {
+                required: vec![regex.clone()],
+                excluded: vec![],
+                highlight: Some(regex),
+            });
+        }
+
+        let required: Vec<String> = query
+            .terms
+            .iter()
+            .map(|t| term_pattern(t, query.mode))
+            .collect();
+
+        let highlight = if required.is_empty() {
+            None
+        } else {
+            Some(compile(&required.join("|"))?)
+        };
+
+        // The FTS index already applied the terms in full-text mode.
+        if query.mode == SearchMode::FullText {
+            return Ok(Self {
+                required: vec![],
+                excluded: vec![],
+                highlight,
+            });
+        }
+
+        Ok(Self {
+            required: required
+                .iter()
+                .map(|p| compile(p))
+                .collect::<Result<_, _>>()?,
+            excluded: query
+                .excluded
+                .iter()
+                .map(|t| compile(&term_pattern(t, query.mode)))
+                .collect::<Result<_, _>>()?,
+            highlight,
+        })
+    }
+
+    /// Every required term has to match in one of the blocks, and no
+    /// excluded term in any of them.
+    pub fn is_match(&self, blocks: &[BlockText]) -> bool {
+        let found = |r: &Regex| blocks.iter().any(|(_, text)| r.is_match(text));
+        self.required.iter().all(found) && !self.excluded.iter().any(found)
+    }
+
+    /// The first block with a highlighted match, or the first block when the
+    /// match only exists after stemming.
+    fn best_block<'a>(&self, blocks: &'a [BlockText]) -> Option<&'a BlockText> {
+        self.highlight
+            .as_ref()
+            .and_then(|r| blocks.iter().find(|(_, text)| r.is_match(text)))
+            .or_else(|| blocks.first())
+    }
+}
+
+/// First page of results for `query`.
+pub fn search_messages(
+    registry: &SourceRegistry,
+    index: &SessionIndex,
+    query: &str,
+    mode: SearchMode,
+) -> Result<Vec<SearchResult>, SearchError> {
+    let request = SearchRequest {
+        query: query.to_string(),
+        mode,
+        cursor: None,
+        limit: DEFAULT_PAGE_SIZE,
+        blocks: vec![],
+    };
+
+    Ok(search_page(registry, index, &request)?.results)
+}
+
+pub fn search_page(
+    registry: &SourceRegistry,
+    index: &SessionIndex,
+    request: &SearchRequest,
+) -> Result<SearchPage, SearchError> {
+    let mut results = vec![];
+    let next_cursor = stream_search(registry, index, request, &mut |batch| {
+        results.extend(batch);
+        true
+    })?;
+
+    Ok(SearchPage {
+        results,
+        next_cursor,
+    })
+}
+
+/// Runs a search and hands its results to `on_batch` in batches as they are
+/// read from the index, stopping early when `on_batch` returns `false`.
+/// Returns the cursor of the next page when there are more hits.
+pub fn stream_search(
+    registry: &SourceRegistry,
+    index: &SessionIndex,
+    request: &SearchRequest,
+    on_batch: &mut dyn FnMut(Vec<SearchResult>) -> bool,
+) -> Result<Option<String>, SearchError> {
+    let mut query = SearchQuery::parse(&request.query, request.mode)?;
+    query.blocks = request.blocks.clone();
+    if query.is_empty() {
+        return Ok(None);
+    }
+
+    let after = request
+        .cursor
+        .as_deref()
+        .map(str::parse::<SearchCursor>)
+        .transpose()?;
+    let matcher = Matcher::new(&query)?;
+
+    index.sync(registry)?;
+
+    let limit = request.limit.max(1);
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut sent = 0;
+    let mut last_cursor = None;
+    let mut next_cursor = None;
+    let mut stopped = false;
+
+    index.search(
+        &query,
+        after.as_ref(),
+        &|blocks| matcher.is_match(blocks),
+        &mut |hit| {
+            // One hit past the page only proves there is a next page.
+            if sent == limit {
+                next_cursor = last_cursor.take();
+                return false;
+            }
+
+            let (block, text) = matcher
+                .best_block(&hit.blocks)
+                .map_or((BlockKind::Text, ""), |(kind, text)| (*kind, text.as_str()));
+            let (content_preview, highlights) =
+                create_preview(text, matcher.highlight.as_ref(), 100);
+            batch.push(SearchResult {
+                block,
+                content_preview,
+                highlights,
+                project_name: hit.project_name,
+                project_path: hit.project_path,
+                session_id: hit.session_id,
+                session_path: hit.session_path,
+                message_uuid: hit.uuid.unwrap_or_default(),
+                role: hit.role.unwrap_or_default(),
+                timestamp: hit.timestamp.unwrap_or_default(),
+                score: hit.score,
+            });
+            sent += 1;
+            last_cursor = Some(hit.cursor.to_string());
+
+            if batch.len() == BATCH_SIZE && !on_batch(std::mem::take(&mut batch)) {
+                stopped = true;
+                return false;
+            }
+            true
+        },
+    )?;
+
+    if !stopped && !batch.is_empty() {
+        on_batch(batch);
+    }
+
+    Ok(next_cursor)
+}
+
+fn default_page_size() -> usize {
+    DEFAULT_PAGE_SIZE
+}
+
+/// Regex source for one literal term. Full-text mode highlights prefix terms
+/// up to the end of the word, like the FTS index matched them.
+fn term_pattern(term: &Term, mode: SearchMode) -> String {
+    let text = term.text();
+    let escaped = regex::escape(text);
+    let prefix = matches!(term, Term::Word { prefix: true, .. });
+
+    match mode {
+        SearchMode::CaseSensitive | SearchMode::Regex => escaped,
+        SearchMode::Substring => format!("(?i:{})", escaped),
+        SearchMode::FullText if prefix => format!("(?i:{}\\w*)", escaped),
+        SearchMode::FullText => format!("(?i:{})", escaped),
+        SearchMode::WholeWord => {
+            // `\b` only means "word boundary" next to a word character.
+            let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+            let start = if is_word(text.chars().next()) {
+                "\\b"
+            } else {
+                ""
+            };
+            let end = if !prefix && is_word(text.chars().last()) {
+                "\\b"
+            } else {
+                ""
+            };
+            format!("(?i:{}{}{})", start, escaped, end)
+        }
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, SearchError> {
+    Regex::new(pattern).map_err(|e| SearchError::InvalidRegex {
+        pattern: pattern.to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// Shows the text around the first match, or the start of the text when the
+/// match only exists after stemming, along with the matches inside it.
+fn create_preview(
+    text: &str,
+    highlight: Option<&Regex>,
+    max_len: usize,
+) -> (String, Vec<MatchSpan>) {
+    let first = highlight.and_then(|r| r.find_iter(text).find(|m| !m.is_empty()));
+
+    let (start, end) = match first {
+        Some(m) => (
+            floor_char_boundary(text, m.start().saturating_sub(30)),
+            floor_char_boundary(
+                text,
+                (m.end() + 70).min(m.start() + max_len).min(text.len()),
+            ),
+        ),
+        None => (
+            0,
+            text.char_indices()
+                .nth(max_len)
+                .map_or(text.len(), |(i, _)| i),
+        ),
+    };
+
+    let ellipsis = if start > 0 { "..." } else { "" };
+    let offset = ellipsis.len();
+    let mut highlights = vec![];
+
+    if let Some(regex) = highlight {
+        for m in regex.find_iter(text) {
+            if m.start() >= end {
+                break;
+            }
+            if m.is_empty() || m.end() <= start {
+                continue;
+            }
+            let (from, to) = (m.start().max(start), m.end().min(end));
+            let chars_before = text[start..from].chars().count();
+            highlights.push(MatchSpan {
+                start: offset + chars_before,
+                end: offset + chars_before + text[from..to].chars().count(),
+            });
+        }
+    }
+
+    let mut preview = format!("{}{}", ellipsis, &text[start..end]);
+    if end < text.len() {
+        preview.push_str("...");
+    }
+
+    (preview.replace('\n', " "), highlights)
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
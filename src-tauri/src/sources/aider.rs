@@ -0,0 +1,365 @@
+use super::{project_display_name, Records, SessionSource, SessionStamp};
+use crate::models::{Message, MessageContent, Project, TokenUsage};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CHAT_HISTORY: &str = ".aider.chat.history.md";
+const INPUT_HISTORY: &str = ".aider.input.history";
+const CHAT_STARTED: &str = "# aider chat started at ";
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Aider's history in one repository: `.aider.chat.history.md`, which gets a
+/// `# aider chat started at` header each time Aider starts, and
+/// `.aider.input.history`, which timestamps every prompt.
+///
+/// The repository is the only project. Each chat in the history file is a
+/// session, addressed as `<repo>/.aider.chat.history.md/<start time>`. One
+/// source is registered per repository in the config's `aider.repos`.
+pub struct AiderSource {
+    /// `aider:<repo>`, so every repository is a source of its own.
+    id: String,
+    root: PathBuf,
+}
+
+/// One `# aider chat started at` section of the history file.
+struct Chat {
+    id: String,
+    started: Option<NaiveDateTime>,
+    lines: Vec<String>,
+}
+
+impl AiderSource {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            id: format!("aider:{}", root.display()),
+            root,
+        }
+    }
+
+    fn history_path(&self) -> PathBuf {
+        self.root.join(CHAT_HISTORY)
+    }
+
+    fn read_chats(&self) -> Result<Vec<Chat>, String> {
+        let content = fs::read_to_string(self.history_path()).map_err(|e| e.to_string())?;
+        Ok(split_chats(&content))
+    }
+}
+
+impl SessionSource for AiderSource {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn list_projects(&self) -> Result<Vec<Project>, String> {
+        let Ok(chats) = self.read_chats() else {
+            return Ok(vec![]);
+        };
+        if chats.is_empty() {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![Project {
+            name: format!("{} (Aider)", project_display_name(&self.root)),
+            path: self.root.to_string_lossy().to_string(),
+            session_count: chats.len(),
+        }])
+    }
+
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String> {
+        if project_path != self.root {
+            return Err("Project path does not exist".to_string());
+        }
+
+        let history = self.history_path();
+
+        Ok(self
+            .read_chats()?
+            .into_iter()
+            .map(|chat| history.join(chat.id))
+            .collect())
+    }
+
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String> {
+        let id = session_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or("Invalid Aider session path")?;
+
+        let chat = self
+            .read_chats()?
+            .into_iter()
+            .find(|chat| chat.id == id)
+            .ok_or_else(|| format!("Aider chat not found: {}", id))?;
+
+        let inputs = fs::read_to_string(self.root.join(INPUT_HISTORY))
+            .map(|content| parse_input_history(&content))
+            .unwrap_or_default();
+
+        Ok(Box::new(chat_messages(&chat, &inputs).into_iter()))
+    }
+
+    /// Both history files, as a new prompt changes either.
+    fn stamp(&self, _session_path: &Path) -> Option<SessionStamp> {
+        let history = SessionStamp::of_file(&self.history_path())?;
+
+        Some(
+            match SessionStamp::of_file(&self.root.join(INPUT_HISTORY)) {
+                Some(inputs) => history.merge(inputs),
+                None => history,
+            },
+        )
+    }
+
+    fn working_dir(&self, _session_path: &Path) -> Option<PathBuf> {
+        Some(self.root.clone())
+    }
+
+    fn owns(&self, path: &Path) -> bool {
+        path == self.root || path.starts_with(self.history_path())
+    }
+}
+
+/// Splits the history file at its `# aider chat started at` headers. Chats
+/// are identified by their start time, e.g. `2025-01-05T10-00-00`, with a
+/// suffix when two started in the same second.
+fn split_chats(content: &str) -> Vec<Chat> {
+    let mut chats: Vec<Chat> = vec![];
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for line in content.lines() {
+        if let Some(started) = line.strip_prefix(CHAT_STARTED) {
+            let started = NaiveDateTime::parse_from_str(started.trim(), TIME_FORMAT).ok();
+            let base = started
+                .map(|s| s.format("%Y-%m-%dT%H-%M-%S").to_string())
+                .unwrap_or_else(|| format!("chat-{}", chats.len() + 1));
+
+            let count = seen.entry(base.clone()).or_default();
+            *count += 1;
+            let id = match *count {
+                1 => base,
+                n => format!("{}-{}", base, n),
+            };
+
+            chats.push(Chat {
+                id,
+                started,
+                lines: vec![],
+            });
+        } else if let Some(chat) = chats.last_mut() {
+            chat.lines.push(line.to_string());
+        }
+    }
+
+    chats
+}
+
+/// Prompts from `.aider.input.history`: a `# <time>` line, then the prompt
+/// with every line prefixed by `+`.
+fn parse_input_history(content: &str) -> Vec<(NaiveDateTime, String)> {
+    let mut inputs: Vec<(NaiveDateTime, Vec<&str>)> = vec![];
+
+    for line in content.lines() {
+        if let Some(time) = line.strip_prefix("# ") {
+            let format = format!("{}%.f", TIME_FORMAT);
+            if let Ok(time) = NaiveDateTime::parse_from_str(time.trim(), &format) {
+                inputs.push((time, vec![]));
+            }
+        } else if let Some(text) = line.strip_prefix('+') {
+            if let Some((_, lines)) = inputs.last_mut() {
+                lines.push(text);
+            }
+        }
+    }
+
+    inputs
+        .into_iter()
+        .map(|(time, lines)| (time, lines.join("\n")))
+        .collect()
+}
+
+#[derive(PartialEq)]
+enum LineKind {
+    User,
+    Assistant,
+    /// Aider's own output, prefixed by `>`: commands run, edits applied,
+    /// token reports.
+    Output,
+}
+
+/// Reads a chat's markdown: `#### ` lines are the user's, `>` lines Aider's
+/// own output, and the rest the model's replies.
+fn chat_messages(chat: &Chat, inputs: &[(NaiveDateTime, String)]) -> Vec<Message> {
+    let mut messages = vec![];
+    let mut model: Option<String> = None;
+    let mut current: Option<(LineKind, Vec<&str>)> = None;
+    // Prompts are timestamped from the input history, searched from the
+    // chat's start; replies get the time of the prompt they answer.
+    let mut time = chat.started;
+    let mut next_input = inputs
+        .iter()
+        .position(|(t, _)| chat.started.is_none_or(|s| *t >= s))
+        .unwrap_or(inputs.len());
+
+    let mut flush = |current: Option<(LineKind, Vec<&str>)>,
+                     messages: &mut Vec<Message>,
+                     time: &mut Option<NaiveDateTime>,
+                     model: &Option<String>| {
+        let Some((kind, lines)) = current else {
+            return;
+        };
+        let text = lines.join("\n").trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+
+        let role = match kind {
+            LineKind::User => {
+                if let Some(offset) = inputs[next_input..]
+                    .iter()
+                    .position(|(_, input)| input.trim() == text)
+                {
+                    next_input += offset;
+                    *time = Some(inputs[next_input].0);
+                    next_input += 1;
+                }
+                "user"
+            }
+            LineKind::Assistant => "assistant",
+            LineKind::Output => return,
+        };
+
+        messages.push(message(role, text, *time, model.clone()));
+    };
+
+    for line in &chat.lines {
+        let (kind, text) = if let Some(text) = line.strip_prefix("#### ") {
+            (LineKind::User, text)
+        } else if line == "####" {
+            (LineKind::User, "")
+        } else if let Some(text) = line.strip_prefix('>') {
+            (LineKind::Output, text.strip_prefix(' ').unwrap_or(text))
+        } else {
+            (LineKind::Assistant, line.as_str())
+        };
+
+        let is_output = kind == LineKind::Output;
+
+        // Blank lines belong to whatever they are in the middle of.
+        let continues = match &current {
+            Some((current_kind, _)) => *current_kind == kind || line.trim().is_empty(),
+            None => false,
+        };
+
+        if continues {
+            if let Some((_, lines)) = current.as_mut() {
+                lines.push(text);
+            }
+        } else {
+            flush(current.take(), &mut messages, &mut time, &model);
+            current = Some((kind, vec![text]));
+        }
+
+        // Read after the reply before it has been flushed.
+        if is_output {
+            if let Some(name) = text
+                .strip_prefix("Main model: ")
+                .or_else(|| text.strip_prefix("Model: "))
+            {
+                model = name.split_whitespace().next().map(String::from);
+            }
+            if let Some(usage) = text.strip_prefix("Tokens: ").and_then(token_report) {
+                if let Some(content) = messages
+                    .iter_mut()
+                    .rev()
+                    .filter_map(|m: &mut Message| m.message.as_mut())
+                    .find(|m| m.role.as_deref() == Some("assistant"))
+                {
+                    content.usage = Some(usage);
+                }
+            }
+        }
+    }
+    flush(current, &mut messages, &mut time, &model);
+
+    messages
+}
+
+fn message(
+    role: &str,
+    text: String,
+    time: Option<NaiveDateTime>,
+    model: Option<String>,
+) -> Message {
+    Message {
+        msg_type: Some(role.to_string()),
+        uuid: None,
+        parent_uuid: None,
+        timestamp: time
+            .and_then(|t| Local.from_local_datetime(&t).earliest())
+            .map(|t| t.to_rfc3339()),
+        session_id: None,
+        is_sidechain: false,
+        tool_calls: vec![],
+        message: Some(MessageContent {
+            role: Some(role.to_string()),
+            content: Some(serde_json::Value::String(text)),
+            model: if role == "assistant" { model } else { None },
+            usage: None,
+        }),
+    }
+}
+
+/// Reads the report Aider prints after each reply, e.g. `2.1k sent, 1.5k
+/// cache hit, 150 received. Cost: $0.01 message, $0.05 session.`
+fn token_report(report: &str) -> Option<TokenUsage> {
+    let (tokens, cost) = match report.split_once(". Cost: $") {
+        Some((tokens, cost)) => (tokens, Some(cost)),
+        None => (report.trim_end_matches('.'), None),
+    };
+
+    let mut usage = TokenUsage {
+        input_tokens: None,
+        output_tokens: None,
+        cache_read_input_tokens: None,
+        cache_creation_input_tokens: None,
+        reasoning_tokens: None,
+        cost: cost
+            .and_then(|c| c.split_whitespace().next())
+            .and_then(|c| c.parse().ok()),
+    };
+
+    for item in tokens.split(", ") {
+        let (count, label) = item.split_once(' ')?;
+        let count = token_count(count)?;
+        match label {
+            "sent" => usage.input_tokens = Some(count),
+            "received" => usage.output_tokens = Some(count),
+            "cache hit" => usage.cache_read_input_tokens = Some(count),
+            "cache write" => usage.cache_creation_input_tokens = Some(count),
+            _ => {}
+        }
+    }
+
+    usage.input_tokens.is_some().then_some(usage)
+}
+
+/// `150`, `2.1k` or `1.2M`.
+fn token_count(count: &str) -> Option<u64> {
+    let (number, scale) = match count.chars().last()? {
+        'k' => (&count[..count.len() - 1], 1_000.0),
+        'M' => (&count[..count.len() - 1], 1_000_000.0),
+        _ => (count, 1.0),
+    };
+
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|n| (n * scale).round() as u64)
+}
@@ -0,0 +1,180 @@
+use super::{
+    jsonl_cwd, list_jsonl_files, project_display_name, read_jsonl_lines, Records, SessionSource,
+    SessionStamp,
+};
+use crate::models::{Message, Project};
+use crate::record::Record;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Claude Code sessions under `~/.claude/projects/<dashed-cwd>/<session>.jsonl`.
+///
+/// The dashed name loses which dashes were slashes, so projects are named
+/// after the `cwd` Claude Code records in the sessions, and the name is only
+/// decoded when no session has one.
+///
+/// Newer Claude Code versions write subagent transcripts next to the session,
+/// to `<session>/subagents/*.jsonl`; their records are read as sidechains of
+/// the session.
+pub struct ClaudeProjectsSource {
+    root: PathBuf,
+}
+
+impl ClaudeProjectsSource {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Working directory of the project's sessions.
+    fn project_dir(&self, project_path: &Path) -> PathBuf {
+        list_jsonl_files(project_path)
+            .unwrap_or_default()
+            .iter()
+            .find_map(|session| jsonl_cwd(session))
+            .unwrap_or_else(|| {
+                let name = project_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("");
+                decode_dashed_dir(name)
+            })
+    }
+}
+
+impl SessionSource for ClaudeProjectsSource {
+    fn id(&self) -> &'static str {
+        "claude-projects"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn list_projects(&self) -> Result<Vec<Project>, String> {
+        if !self.root.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut projects = vec![];
+
+        let entries = fs::read_dir(&self.root).map_err(|e| e.to_string())?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            let session_count = list_jsonl_files(&path).map(|f| f.len()).unwrap_or(0);
+
+            if session_count > 0 {
+                projects.push(Project {
+                    name: project_display_name(&self.project_dir(&path)),
+                    path: path.to_string_lossy().to_string(),
+                    session_count,
+                });
+            }
+        }
+
+        Ok(projects)
+    }
+
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String> {
+        if !project_path.exists() {
+            return Err("Project path does not exist".to_string());
+        }
+
+        list_jsonl_files(project_path)
+    }
+
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String> {
+        if !session_path.exists() {
+            return Err("Session file does not exist".to_string());
+        }
+
+        let main = read_jsonl_lines(session_path)?
+            .filter_map(|line| serde_json::from_str::<Message>(&line).ok());
+
+        let subagents = subagent_files(session_path)
+            .into_iter()
+            .filter_map(|path| read_jsonl_lines(&path).ok())
+            .flatten()
+            .filter_map(|line| serde_json::from_str::<Message>(&line).ok())
+            .map(|mut msg| {
+                msg.is_sidechain = true;
+                msg
+            });
+
+        Ok(Box::new(main.chain(subagents)))
+    }
+
+    /// Reads the session file itself, so summaries, system notices and
+    /// snapshots are kept too.
+    fn load_records(&self, session_path: &Path) -> Result<Vec<Record>, String> {
+        if !session_path.exists() {
+            return Err("Session file does not exist".to_string());
+        }
+
+        Ok(read_jsonl_lines(session_path)?
+            .filter_map(|line| Record::parse(&line))
+            .filter(|record| !record.is_sidechain())
+            .collect())
+    }
+
+    fn working_dir(&self, session_path: &Path) -> Option<PathBuf> {
+        jsonl_cwd(session_path).or_else(|| Some(self.project_dir(session_path.parent()?)))
+    }
+
+    fn stamp(&self, session_path: &Path) -> Option<SessionStamp> {
+        let session = SessionStamp::of_file(session_path)?;
+
+        Some(
+            subagent_files(session_path)
+                .iter()
+                .filter_map(|path| SessionStamp::of_file(path))
+                .fold(session, SessionStamp::merge),
+        )
+    }
+}
+
+fn subagent_files(session_path: &Path) -> Vec<PathBuf> {
+    let dir = session_path.with_extension("").join("subagents");
+    let mut files = list_jsonl_files(&dir).unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Recovers a working directory from Claude Code's dashed directory name, e.g.
+/// `-home-me-agent-log`. Dashes are taken as part of a name where that names a
+/// directory that exists, and `--` as the start of a hidden one; otherwise
+/// every dash is a slash.
+pub fn decode_dashed_dir(name: &str) -> PathBuf {
+    let parts: Vec<&str> = name.trim_start_matches('-').split('-').collect();
+
+    resolve_dashed_parts(Path::new("/"), &parts)
+        .unwrap_or_else(|| Path::new("/").join(parts.join("/")))
+}
+
+/// Splits `parts` into the longest existing directory names below `base`.
+fn resolve_dashed_parts(base: &Path, parts: &[&str]) -> Option<PathBuf> {
+    if parts.is_empty() {
+        return Some(base.to_path_buf());
+    }
+
+    (1..=parts.len()).rev().find_map(|end| {
+        let name = parts[..end].join("-");
+        let name = match name.strip_prefix('-') {
+            Some(hidden) => format!(".{}", hidden),
+            None => name,
+        };
+        if name.is_empty() || name == "." {
+            return None;
+        }
+
+        let dir = base.join(name);
+        if !dir.is_dir() {
+            return None;
+        }
+        resolve_dashed_parts(&dir, &parts[end..])
+    })
+}
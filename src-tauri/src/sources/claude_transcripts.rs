@@ -0,0 +1,175 @@
+use super::{
+    is_displayable, list_jsonl_files, read_jsonl_lines, OpenCodeStorageSource, Records,
+    SessionSource, SessionStamp,
+};
+use crate::models::{Message, MessageContent, Project};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Deserialize)]
+struct TranscriptMessage {
+    #[serde(rename = "type")]
+    msg_type: Option<String>,
+    timestamp: Option<String>,
+    content: Option<String>,
+}
+
+/// OpenCode sessions mirrored into `~/.claude/transcripts/<session>.jsonl`.
+///
+/// Transcripts only carry the user's prompts, so records are read from
+/// OpenCode's own storage whenever it still has the session. Sessions that
+/// [`OpenCodeStorageSource`] lists itself are left out here, so they are not
+/// counted twice.
+pub struct ClaudeTranscriptsSource {
+    root: PathBuf,
+    storage: OpenCodeStorageSource,
+}
+
+impl ClaudeTranscriptsSource {
+    pub fn new(root: PathBuf, storage: OpenCodeStorageSource) -> Self {
+        Self { root, storage }
+    }
+
+    fn storage_session(&self, session_path: &Path) -> Option<PathBuf> {
+        let session_id = session_path.file_stem()?.to_str()?;
+        let storage_session = self.storage.session_dir(session_id);
+        storage_session.exists().then_some(storage_session)
+    }
+}
+
+impl SessionSource for ClaudeTranscriptsSource {
+    fn id(&self) -> &'static str {
+        "claude-transcripts"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn list_projects(&self) -> Result<Vec<Project>, String> {
+        if !self.root.exists() {
+            return Ok(vec![]);
+        }
+
+        let session_count = self
+            .list_session_paths(&self.root)
+            .map(|f| f.len())
+            .unwrap_or(0);
+
+        if session_count == 0 {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![Project {
+            name: "OpenCode Sessions".to_string(),
+            path: self.root.to_string_lossy().to_string(),
+            session_count,
+        }])
+    }
+
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String> {
+        if !project_path.exists() {
+            return Err("Project path does not exist".to_string());
+        }
+
+        Ok(list_jsonl_files(project_path)?
+            .into_iter()
+            .filter(|path| {
+                !path
+                    .file_stem()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|id| self.storage.lists_session(id))
+            })
+            .collect())
+    }
+
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String> {
+        if !session_path.exists() {
+            return Err("Session file does not exist".to_string());
+        }
+
+        if let Some(storage_session) = self.storage_session(session_path) {
+            return self.storage.records(&storage_session);
+        }
+
+        Ok(Box::new(
+            read_jsonl_lines(session_path)?.filter_map(|line| parse_transcript_line(&line)),
+        ))
+    }
+
+    /// Known only for sessions OpenCode still has in storage.
+    fn working_dir(&self, session_path: &Path) -> Option<PathBuf> {
+        self.storage
+            .working_dir(&self.storage_session(session_path)?)
+    }
+
+    fn stamp(&self, session_path: &Path) -> Option<SessionStamp> {
+        let transcript = SessionStamp::of_file(session_path)?;
+
+        Some(match self.storage_session(session_path) {
+            Some(storage_session) => self
+                .storage
+                .stamp(&storage_session)
+                .map_or(transcript, |storage| transcript.merge(storage)),
+            None => transcript,
+        })
+    }
+}
+
+fn parse_transcript_line(line: &str) -> Option<Message> {
+    if let Ok(msg) = serde_json::from_str::<Message>(line) {
+        if is_displayable(&msg) {
+            return Some(msg);
+        }
+    }
+
+    let transcript_msg = serde_json::from_str::<TranscriptMessage>(line).ok()?;
+    if transcript_msg.msg_type.as_deref() != Some("user") {
+        return None;
+    }
+
+    let cleaned_content = clean_transcript_content(transcript_msg.content.as_deref()?);
+    if cleaned_content.is_empty() {
+        return None;
+    }
+
+    Some(Message {
+        msg_type: transcript_msg.msg_type,
+        uuid: None,
+        parent_uuid: None,
+        timestamp: transcript_msg.timestamp,
+        session_id: None,
+        is_sidechain: false,
+        tool_calls: vec![],
+        message: Some(MessageContent {
+            role: Some("user".to_string()),
+            content: Some(serde_json::Value::String(cleaned_content)),
+            model: None,
+            usage: None,
+        }),
+    })
+}
+
+/// Strips the system reminders and background task notices OpenCode injects
+/// into user prompts.
+fn clean_transcript_content(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            !trimmed.starts_with("<system-reminder>")
+                && !trimmed.starts_with("[BACKGROUND TASK")
+                && !trimmed.starts_with("[ALL BACKGROUND TASKS")
+                && !trimmed.starts_with("**ID:**")
+                && !trimmed.starts_with("**Description:**")
+                && !trimmed.starts_with("**Duration:**")
+                && !trimmed.starts_with("Use `background_output")
+                && !trimmed.starts_with("Do NOT poll")
+                && !trimmed.contains("</system-reminder>")
+                && !trimmed.contains("still in progress")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
@@ -0,0 +1,267 @@
+use super::{Records, SessionSource, SessionStamp};
+use crate::models::{Message, MessageContent, Project, TokenUsage};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const API_HISTORY: &str = "api_conversation_history.json";
+const UI_MESSAGES: &str = "ui_messages.json";
+
+/// Longest session title, in characters.
+const TITLE_LENGTH: usize = 80;
+
+#[derive(Debug, Deserialize)]
+struct ApiMessage {
+    role: String,
+    #[serde(default)]
+    content: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct UiMessage {
+    /// Milliseconds since the Unix epoch.
+    ts: i64,
+    say: Option<String>,
+    text: Option<String>,
+}
+
+/// What Cline records about each API request, as the JSON text of its
+/// `api_req_started` message.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ApiRequest {
+    tokens_in: Option<u64>,
+    tokens_out: Option<u64>,
+    cache_writes: Option<u64>,
+    cache_reads: Option<u64>,
+    cost: Option<f64>,
+}
+
+/// Tasks of Cline, or of a fork of it like Roo Code, in the extension's VS
+/// Code global storage, `globalStorage/<extension>/tasks/<task_id>/`.
+///
+/// Each task directory is a session, read from `api_conversation_history.json`,
+/// the conversation in Anthropic's message format, and `ui_messages.json`,
+/// which timestamps every API request and records its tokens and cost. The
+/// whole directory is one project.
+pub struct ClineSource {
+    id: &'static str,
+    name: &'static str,
+    root: PathBuf,
+}
+
+impl ClineSource {
+    pub fn cline(root: PathBuf) -> Self {
+        Self {
+            id: "cline",
+            name: "Cline",
+            root,
+        }
+    }
+
+    pub fn roo_code(root: PathBuf) -> Self {
+        Self {
+            id: "roo-code",
+            name: "Roo Code",
+            root,
+        }
+    }
+}
+
+impl SessionSource for ClineSource {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn list_projects(&self) -> Result<Vec<Project>, String> {
+        if !self.root.exists() {
+            return Ok(vec![]);
+        }
+
+        let session_count = self
+            .list_session_paths(&self.root)
+            .map(|s| s.len())
+            .unwrap_or(0);
+
+        if session_count == 0 {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![Project {
+            name: format!("{} Tasks", self.name),
+            path: self.root.to_string_lossy().to_string(),
+            session_count,
+        }])
+    }
+
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String> {
+        if !project_path.exists() {
+            return Err("Project path does not exist".to_string());
+        }
+
+        let entries = fs::read_dir(project_path).map_err(|e| e.to_string())?;
+
+        Ok(entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.join(API_HISTORY).is_file())
+            .collect())
+    }
+
+    /// The task the user gave, shortened to its first line.
+    fn session_title(&self, session_path: &Path) -> Option<String> {
+        let task = read_ui_messages(session_path)
+            .into_iter()
+            .find(|m| m.say.as_deref() == Some("task"))?
+            .text?;
+        let line = task.lines().find(|l| !l.trim().is_empty())?.trim();
+
+        Some(match line.char_indices().nth(TITLE_LENGTH) {
+            Some((end, _)) => format!("{}…", &line[..end]),
+            None => line.to_string(),
+        })
+    }
+
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String> {
+        let content =
+            fs::read_to_string(session_path.join(API_HISTORY)).map_err(|e| e.to_string())?;
+        let history: Vec<ApiMessage> = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", API_HISTORY, e))?;
+
+        let task_id = session_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(String::from);
+
+        // Requests and conversation messages line up: the n-th request sends
+        // the n-th user message and gets the n-th assistant message back.
+        let requests: Vec<(i64, ApiRequest)> = read_ui_messages(session_path)
+            .into_iter()
+            .filter(|m| m.say.as_deref() == Some("api_req_started"))
+            .map(|m| {
+                let request = m
+                    .text
+                    .and_then(|t| serde_json::from_str(&t).ok())
+                    .unwrap_or_default();
+                (m.ts, request)
+            })
+            .collect();
+
+        let mut user_turns = 0;
+        let mut assistant_turns = 0;
+
+        let messages: Vec<Message> = history
+            .into_iter()
+            .filter_map(|msg| {
+                let turn = match msg.role.as_str() {
+                    "user" => &mut user_turns,
+                    "assistant" => &mut assistant_turns,
+                    _ => return None,
+                };
+                let request = requests.get(*turn);
+                *turn += 1;
+
+                let usage = match msg.role.as_str() {
+                    "assistant" => request.and_then(|(_, r)| token_usage(r)),
+                    _ => None,
+                };
+
+                Some(Message {
+                    msg_type: Some(msg.role.clone()),
+                    uuid: None,
+                    parent_uuid: None,
+                    timestamp: request
+                        .and_then(|(ts, _)| chrono::DateTime::from_timestamp_millis(*ts))
+                        .map(|dt| dt.to_rfc3339()),
+                    session_id: task_id.clone(),
+                    is_sidechain: false,
+                    tool_calls: vec![],
+                    message: Some(MessageContent {
+                        role: Some(msg.role),
+                        content: Some(clean_content(msg.content)),
+                        model: None,
+                        usage,
+                    }),
+                })
+            })
+            .collect();
+
+        Ok(Box::new(messages.into_iter()))
+    }
+
+    fn stamp(&self, session_path: &Path) -> Option<SessionStamp> {
+        let history = SessionStamp::of_file(&session_path.join(API_HISTORY))?;
+
+        Some(
+            match SessionStamp::of_file(&session_path.join(UI_MESSAGES)) {
+                Some(ui) => history.merge(ui),
+                None => history,
+            },
+        )
+    }
+}
+
+fn read_ui_messages(session_path: &Path) -> Vec<UiMessage> {
+    fs::read_to_string(session_path.join(UI_MESSAGES))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn token_usage(request: &ApiRequest) -> Option<TokenUsage> {
+    if request.tokens_in.is_none() && request.tokens_out.is_none() {
+        return None;
+    }
+
+    Some(TokenUsage {
+        input_tokens: request.tokens_in,
+        output_tokens: request.tokens_out,
+        cache_read_input_tokens: request.cache_reads,
+        cache_creation_input_tokens: request.cache_writes,
+        reasoning_tokens: None,
+        cost: request.cost,
+    })
+}
+
+/// Drops the `<environment_details>` Cline appends to every user message and
+/// unwraps the `<task>` and `<feedback>` tags around what the user typed.
+fn clean_content(content: Value) -> Value {
+    let clean_text = |text: &str| {
+        let mut text = text.trim();
+        for tag in ["task", "feedback"] {
+            if let Some(inner) = text
+                .strip_prefix(&format!("<{}>", tag))
+                .and_then(|t| t.strip_suffix(&format!("</{}>", tag)))
+            {
+                text = inner.trim();
+            }
+        }
+        text.to_string()
+    };
+
+    match content {
+        Value::String(text) => Value::String(clean_text(&text)),
+        Value::Array(blocks) => Value::Array(
+            blocks
+                .into_iter()
+                .filter_map(|mut block| {
+                    let Some(text) = block.get("text").and_then(|t| t.as_str()) else {
+                        return Some(block);
+                    };
+                    if text.trim_start().starts_with("<environment_details>") {
+                        return None;
+                    }
+                    let text = clean_text(text);
+                    block["text"] = Value::String(text);
+                    Some(block)
+                })
+                .collect(),
+        ),
+        other => other,
+    }
+}
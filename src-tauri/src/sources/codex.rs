@@ -0,0 +1,544 @@
+use super::{is_jsonl, read_jsonl_lines, Records, SessionSource};
+use crate::models::{Message, MessageContent, Project, TokenUsage};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Lines of a rollout searched for the working directory.
+const METADATA_LINES: usize = 50;
+
+/// Prompts Codex adds to the conversation itself rather than the user typing
+/// them.
+const INJECTED_PROMPTS: [&str; 3] = [
+    "<environment_context>",
+    "<user_instructions>",
+    "# AGENTS.md instructions",
+];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CodexTokens {
+    input_tokens: u64,
+    cached_input_tokens: u64,
+    output_tokens: u64,
+    reasoning_output_tokens: u64,
+}
+
+/// OpenAI Codex CLI sessions, written as rollout files to
+/// `~/.codex/sessions/YYYY/MM/DD/rollout-<time>-<id>.jsonl`.
+///
+/// Rollouts are not stored by project, so projects are the working
+/// directories recorded in the sessions' metadata. A project's path is that
+/// directory under the root, e.g. `~/.codex/sessions/home/me/app`, which does
+/// not exist on disk; sessions without a working directory belong to the
+/// root itself.
+pub struct CodexSource {
+    root: PathBuf,
+    /// Working directory of every rollout it was found in.
+    cwds: Mutex<HashMap<PathBuf, String>>,
+    grouping: Mutex<Option<Grouping>>,
+}
+
+/// Rollouts by project, with what is checked to tell whether it still holds.
+struct Grouping {
+    /// Every directory below the root with its modification time, which
+    /// changes when a rollout is added or removed.
+    dirs: Vec<(PathBuf, Option<SystemTime>)>,
+    /// Rollouts without a working directory yet, by size, as Codex may not
+    /// have written their metadata when they were grouped.
+    unresolved: Vec<(PathBuf, u64)>,
+    projects: BTreeMap<PathBuf, (Option<String>, Vec<PathBuf>)>,
+}
+
+impl CodexSource {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            cwds: Mutex::default(),
+            grouping: Mutex::default(),
+        }
+    }
+
+    fn project_path(&self, cwd: Option<&str>) -> PathBuf {
+        let mut path = self.root.clone();
+        if let Some(cwd) = cwd {
+            path.extend(Path::new(cwd).components().filter_map(|c| match c {
+                Component::Normal(part) => Some(part),
+                _ => None,
+            }));
+        }
+        path
+    }
+
+    /// Rollout files by the project they belong to. Listing projects and
+    /// then the sessions of each asks for this many times over, so it is
+    /// only worked out again when a rollout was added or removed.
+    fn sessions_by_project(&self) -> BTreeMap<PathBuf, (Option<String>, Vec<PathBuf>)> {
+        let dirs = directory_times(&self.root);
+        let mut grouping = self.grouping.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(grouping) = grouping.as_ref() {
+            let unchanged = grouping.dirs == dirs
+                && grouping
+                    .unresolved
+                    .iter()
+                    .all(|(path, size)| file_size(path) == *size);
+            if unchanged {
+                return grouping.projects.clone();
+            }
+        }
+
+        let mut cwds = self.cwds.lock().unwrap_or_else(|e| e.into_inner());
+        let mut unresolved = vec![];
+        let mut projects: BTreeMap<PathBuf, (Option<String>, Vec<PathBuf>)> = BTreeMap::new();
+
+        for path in rollout_files(&self.root) {
+            let cwd = match cwds.get(&path) {
+                Some(cwd) => Some(cwd.clone()),
+                None => {
+                    let cwd = session_cwd(&path);
+                    match &cwd {
+                        Some(cwd) => {
+                            cwds.insert(path.clone(), cwd.clone());
+                        }
+                        None => unresolved.push((path.clone(), file_size(&path))),
+                    }
+                    cwd
+                }
+            };
+
+            projects
+                .entry(self.project_path(cwd.as_deref()))
+                .or_insert_with(|| (cwd, vec![]))
+                .1
+                .push(path);
+        }
+
+        *grouping = Some(Grouping {
+            dirs,
+            unresolved,
+            projects: projects.clone(),
+        });
+
+        projects
+    }
+}
+
+impl SessionSource for CodexSource {
+    fn id(&self) -> &'static str {
+        "codex"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn list_projects(&self) -> Result<Vec<Project>, String> {
+        if !self.root.exists() {
+            return Ok(vec![]);
+        }
+
+        Ok(self
+            .sessions_by_project()
+            .into_iter()
+            .map(|(path, (cwd, sessions))| Project {
+                name: match cwd.as_deref().map(|c| c.trim_matches('/')) {
+                    Some("") | None => "Codex".to_string(),
+                    Some(cwd) => format!("{} (Codex)", cwd),
+                },
+                path: path.to_string_lossy().to_string(),
+                session_count: sessions.len(),
+            })
+            .collect())
+    }
+
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String> {
+        if !self.root.exists() {
+            return Err("Codex sessions directory does not exist".to_string());
+        }
+
+        Ok(self
+            .sessions_by_project()
+            .remove(project_path)
+            .map(|(_, sessions)| sessions)
+            .unwrap_or_default())
+    }
+
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String> {
+        if !session_path.exists() {
+            return Err("Session file does not exist".to_string());
+        }
+
+        Ok(Box::new(read_rollout(session_path)?.into_iter()))
+    }
+
+    fn working_dir(&self, session_path: &Path) -> Option<PathBuf> {
+        session_cwd(session_path).map(PathBuf::from)
+    }
+}
+
+/// Every `rollout-*.jsonl` below `dir`.
+fn rollout_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    let mut files = vec![];
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.is_dir() {
+            files.extend(rollout_files(&path));
+        } else if is_jsonl(&path)
+            && path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("rollout-"))
+        {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// `dir` and every directory below it, with their modification times.
+fn directory_times(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut dirs = vec![(
+        dir.to_path_buf(),
+        fs::metadata(dir).and_then(|m| m.modified()).ok(),
+    )];
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        let mut children: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect();
+        children.sort();
+        for child in children {
+            dirs.extend(directory_times(&child));
+        }
+    }
+    dirs
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Splits a rollout line into its kind, payload and timestamp. Rollouts from
+/// before Codex wrapped every line in `{timestamp, type, payload}` hold the
+/// bare response items, read as `response_item`.
+fn rollout_item(line: &str) -> Option<(String, Value, Option<String>)> {
+    let mut value: Value = serde_json::from_str(line).ok()?;
+    let timestamp = value
+        .get("timestamp")
+        .and_then(|t| t.as_str())
+        .map(String::from);
+
+    match value.get_mut("payload").map(Value::take) {
+        Some(payload) => {
+            let kind = value.get("type")?.as_str()?.to_string();
+            Some((kind, payload, timestamp))
+        }
+        None if value.get("record_type").is_some() => None,
+        None if value.get("type").is_some() => {
+            Some(("response_item".to_string(), value, timestamp))
+        }
+        None => Some(("session_meta".to_string(), value, timestamp)),
+    }
+}
+
+/// Working directory from the session metadata or turn context, or, in old
+/// rollouts, the `<cwd>` of the environment context Codex sends first.
+fn session_cwd(path: &Path) -> Option<String> {
+    for line in read_jsonl_lines(path).ok()?.take(METADATA_LINES) {
+        let Some((kind, payload, _)) = rollout_item(&line) else {
+            continue;
+        };
+
+        if kind == "session_meta" || kind == "turn_context" {
+            if let Some(cwd) = payload.get("cwd").and_then(|c| c.as_str()) {
+                return Some(cwd.to_string());
+            }
+        }
+
+        let text = content_text(&payload);
+        if let Some(start) = text.find("<cwd>") {
+            let rest = &text[start + "<cwd>".len()..];
+            if let Some(end) = rest.find("</cwd>") {
+                return Some(rest[..end].trim().to_string());
+            }
+        }
+    }
+
+    None
+}
+
+fn read_rollout(path: &Path) -> Result<Vec<Message>, String> {
+    let mut messages: Vec<Message> = vec![];
+    let mut session_id = None;
+    let mut model = None;
+    let mut last_totals = CodexTokens::default();
+
+    for line in read_jsonl_lines(path)? {
+        let Some((kind, payload, timestamp)) = rollout_item(&line) else {
+            continue;
+        };
+
+        match kind.as_str() {
+            "session_meta" => {
+                session_id = payload.get("id").and_then(|i| i.as_str()).map(String::from);
+            }
+            "turn_context" => {
+                if let Some(m) = payload.get("model").and_then(|m| m.as_str()) {
+                    model = Some(m.to_string());
+                }
+            }
+            "response_item" => {
+                if let Some(mut msg) = response_item_message(&payload, timestamp) {
+                    msg.session_id = session_id.clone();
+                    if let Some(content) = msg.message.as_mut() {
+                        if content.role.as_deref() == Some("assistant") {
+                            content.model = model.clone();
+                        }
+                    }
+                    messages.push(msg);
+                }
+            }
+            "event_msg" if payload.get("type").and_then(|t| t.as_str()) == Some("token_count") => {
+                let Some(totals) = payload
+                    .get("info")
+                    .and_then(|i| i.get("total_token_usage"))
+                    .and_then(|t| CodexTokens::deserialize(t).ok())
+                else {
+                    continue;
+                };
+
+                // Codex repeats the same totals in several events per turn,
+                // so each turn's usage is what the totals grew by.
+                let usage = token_usage(&totals, &last_totals);
+                last_totals = totals;
+
+                let Some(usage) = usage else {
+                    continue;
+                };
+                if let Some(content) = messages
+                    .iter_mut()
+                    .rev()
+                    .filter_map(|m| m.message.as_mut())
+                    .find(|m| m.role.as_deref() == Some("assistant"))
+                {
+                    content.usage = Some(match content.usage.take() {
+                        Some(previous) => add_usage(previous, usage),
+                        None => usage,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Converts a response item to a message with Claude-style content blocks,
+/// so tool calls are paired with their output like Claude's are.
+fn response_item_message(item: &Value, timestamp: Option<String>) -> Option<Message> {
+    let field = |key: &str| item.get(key).and_then(|v| v.as_str());
+    let call_id = || {
+        field("call_id")
+            .or(field("id"))
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let (role, blocks) = match field("type")? {
+        "message" => {
+            let role = field("role")?;
+            if role != "user" && role != "assistant" {
+                return None;
+            }
+
+            let blocks: Vec<Value> = item
+                .get("content")?
+                .as_array()?
+                .iter()
+                .filter_map(|part| match part.get("type")?.as_str()? {
+                    "input_text" | "output_text" => {
+                        Some(json!({ "type": "text", "text": part.get("text")? }))
+                    }
+                    "input_image" => Some(json!({
+                        "type": "image",
+                        "source": { "type": "url", "url": part.get("image_url")? },
+                    })),
+                    _ => None,
+                })
+                .collect();
+
+            let text = content_text(item);
+            if role == "user"
+                && INJECTED_PROMPTS
+                    .iter()
+                    .any(|prefix| text.trim_start().starts_with(prefix))
+            {
+                return None;
+            }
+
+            (role, blocks)
+        }
+        "reasoning" => {
+            let blocks = item
+                .get("summary")?
+                .as_array()?
+                .iter()
+                .filter_map(|s| Some(json!({ "type": "thinking", "thinking": s.get("text")? })))
+                .collect();
+            ("assistant", blocks)
+        }
+        "function_call" => {
+            let arguments = field("arguments").unwrap_or_default();
+            let input = serde_json::from_str(arguments)
+                .unwrap_or_else(|_| Value::String(arguments.to_string()));
+            let block = json!({
+                "type": "tool_use",
+                "id": call_id(),
+                "name": field("name")?,
+                "input": input,
+            });
+            ("assistant", vec![block])
+        }
+        "custom_tool_call" => {
+            let block = json!({
+                "type": "tool_use",
+                "id": call_id(),
+                "name": field("name")?,
+                "input": item.get("input").cloned().unwrap_or_default(),
+            });
+            ("assistant", vec![block])
+        }
+        "local_shell_call" => {
+            let block = json!({
+                "type": "tool_use",
+                "id": call_id(),
+                "name": "shell",
+                "input": item.get("action").cloned().unwrap_or_default(),
+            });
+            ("assistant", vec![block])
+        }
+        "function_call_output" | "custom_tool_call_output" => {
+            let (output, is_error) = tool_output(item.get("output")?);
+            let block = json!({
+                "type": "tool_result",
+                "tool_use_id": call_id(),
+                "content": output,
+                "is_error": is_error,
+            });
+            ("user", vec![block])
+        }
+        _ => return None,
+    };
+
+    if blocks.is_empty() {
+        return None;
+    }
+
+    Some(Message {
+        msg_type: Some(role.to_string()),
+        uuid: None,
+        parent_uuid: None,
+        timestamp,
+        session_id: None,
+        is_sidechain: false,
+        tool_calls: vec![],
+        message: Some(MessageContent {
+            role: Some(role.to_string()),
+            content: Some(Value::Array(blocks)),
+            model: None,
+            usage: None,
+        }),
+    })
+}
+
+/// Shell calls report `{"output": ..., "metadata": {"exit_code": ...}}` as a
+/// JSON string; other tools return plain text.
+fn tool_output(output: &Value) -> (String, bool) {
+    let text = match output {
+        Value::String(s) => s.clone(),
+        Value::Object(o) => match o.get("content") {
+            Some(Value::String(s)) => s.clone(),
+            _ => output.to_string(),
+        },
+        other => other.to_string(),
+    };
+
+    match serde_json::from_str::<Value>(&text) {
+        Ok(parsed) if parsed.get("output").is_some_and(Value::is_string) => {
+            let exit_code = parsed
+                .get("metadata")
+                .and_then(|m| m.get("exit_code"))
+                .and_then(|c| c.as_i64());
+            (
+                parsed["output"].as_str().unwrap_or_default().to_string(),
+                exit_code.is_some_and(|c| c != 0),
+            )
+        }
+        _ => (text, false),
+    }
+}
+
+/// Text of a `message` item's content.
+fn content_text(item: &Value) -> String {
+    item.get("content")
+        .and_then(|c| c.as_array())
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// Usage between two running totals. Codex counts cached input as part of
+/// the input and reasoning as part of the output, so the input here leaves
+/// out the cached tokens, as Claude's does.
+fn token_usage(totals: &CodexTokens, previous: &CodexTokens) -> Option<TokenUsage> {
+    let input = totals.input_tokens.saturating_sub(previous.input_tokens);
+    let cached = totals
+        .cached_input_tokens
+        .saturating_sub(previous.cached_input_tokens);
+    let output = totals.output_tokens.saturating_sub(previous.output_tokens);
+    let reasoning = totals
+        .reasoning_output_tokens
+        .saturating_sub(previous.reasoning_output_tokens);
+
+    if input == 0 && output == 0 {
+        return None;
+    }
+
+    Some(TokenUsage {
+        input_tokens: Some(input.saturating_sub(cached)),
+        output_tokens: Some(output),
+        cache_read_input_tokens: Some(cached),
+        cache_creation_input_tokens: None,
+        reasoning_tokens: Some(reasoning),
+        cost: None,
+    })
+}
+
+fn add_usage(a: TokenUsage, b: TokenUsage) -> TokenUsage {
+    let sum = |x: Option<u64>, y: Option<u64>| Some(x.unwrap_or(0) + y.unwrap_or(0));
+
+    TokenUsage {
+        input_tokens: sum(a.input_tokens, b.input_tokens),
+        output_tokens: sum(a.output_tokens, b.output_tokens),
+        cache_read_input_tokens: sum(a.cache_read_input_tokens, b.cache_read_input_tokens),
+        cache_creation_input_tokens: None,
+        reasoning_tokens: sum(a.reasoning_tokens, b.reasoning_tokens),
+        cost: None,
+    }
+}
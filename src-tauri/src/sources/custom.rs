@@ -0,0 +1,369 @@
+use super::{list_jsonl_files, LineError, Records, SessionSource, SessionStamp};
+use crate::models::{value_text, Message, MessageContent, Project, TokenUsage};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How to read the JSONL logs of an agent this app has no reader for. Loaded
+/// from one of the files listed in the config's `custom`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Mapping {
+    /// Name of the project the logs are listed under.
+    pub name: String,
+    /// Directory holding the `*.jsonl` logs.
+    pub root: PathBuf,
+    pub fields: FieldPaths,
+    /// Role names of the logs mapped to `user` and `assistant`, e.g.
+    /// `{"human": "user", "ai": "assistant"}`.
+    #[serde(default)]
+    pub roles: HashMap<String, String>,
+}
+
+/// JSON pointers (RFC 6901) to each field within a line, e.g.
+/// `/message/role`. Only `role` and `text` are required.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldPaths {
+    pub role: String,
+    /// A string, or an array of strings or `{"text": ...}` blocks.
+    pub text: String,
+    /// An RFC 3339 string, or seconds or milliseconds since the Unix epoch.
+    pub timestamp: Option<String>,
+    /// When set, a file holds as many sessions as it has IDs.
+    pub session_id: Option<String>,
+    pub model: Option<String>,
+    pub input_tokens: Option<String>,
+    pub output_tokens: Option<String>,
+    pub cache_read_tokens: Option<String>,
+    pub cache_write_tokens: Option<String>,
+    pub cost: Option<String>,
+}
+
+impl Mapping {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mapping: Mapping = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+        let fields = &mapping.fields;
+        let pointers = [
+            Some(&fields.role),
+            Some(&fields.text),
+            fields.timestamp.as_ref(),
+            fields.session_id.as_ref(),
+            fields.model.as_ref(),
+            fields.input_tokens.as_ref(),
+            fields.output_tokens.as_ref(),
+            fields.cache_read_tokens.as_ref(),
+            fields.cache_write_tokens.as_ref(),
+            fields.cost.as_ref(),
+        ];
+        if let Some(invalid) = pointers
+            .into_iter()
+            .flatten()
+            .find(|p| !p.is_empty() && !p.starts_with('/'))
+        {
+            return Err(format!(
+                "\"{}\" is not a JSON pointer; it must start with /",
+                invalid
+            ));
+        }
+
+        Ok(mapping)
+    }
+}
+
+/// A line's number, and its JSON or why it is not JSON.
+type Line = (usize, Result<Value, String>);
+
+/// JSONL logs read through a [`Mapping`]. Each file is a session, or, when
+/// `session_id` is mapped, each ID in a file is, addressed as
+/// `<file>/<session_id>` with `%`, `/` and `\` in the ID percent-encoded.
+pub struct CustomSource {
+    /// `custom:<name>`, so every mapping is a source of its own.
+    id: String,
+    mapping: Mapping,
+}
+
+impl CustomSource {
+    pub fn new(mapping: Mapping) -> Self {
+        Self {
+            id: format!("custom:{}", mapping.name),
+            mapping,
+        }
+    }
+
+    /// The log file a session is read from.
+    fn session_file<'a>(&self, session_path: &'a Path) -> &'a Path {
+        if self.mapping.fields.session_id.is_some() && !session_path.is_file() {
+            session_path.parent().unwrap_or(session_path)
+        } else {
+            session_path
+        }
+    }
+
+    /// The session ID a path addresses, if it is not a whole file.
+    fn session_id(&self, session_path: &Path) -> Option<String> {
+        if self.session_file(session_path) == session_path {
+            return None;
+        }
+        session_path.file_name()?.to_str().map(decode_session_id)
+    }
+
+    fn line_session_id(&self, value: &Value) -> Option<String> {
+        let pointer = self.mapping.fields.session_id.as_ref()?;
+        match value.pointer(pointer)? {
+            Value::String(id) => Some(id.clone()),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Lines of the session's file with their line numbers, parsed, or the
+    /// reason a line is not JSON. Lines of the file's other sessions are left
+    /// out.
+    fn session_lines(&self, session_path: &Path) -> Result<Vec<Line>, String> {
+        let content =
+            fs::read_to_string(self.session_file(session_path)).map_err(|e| e.to_string())?;
+        let session_id = self.session_id(session_path);
+
+        Ok(content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| (i + 1, serde_json::from_str::<Value>(line)))
+            .filter(|(_, value)| match (&session_id, value) {
+                (Some(id), Ok(value)) => self.line_session_id(value).as_ref() == Some(id),
+                _ => true,
+            })
+            .map(|(number, value)| (number, value.map_err(|e| format!("not JSON: {}", e))))
+            .collect())
+    }
+
+    fn message(&self, value: &Value) -> Result<Message, String> {
+        let fields = &self.mapping.fields;
+
+        let role = match value.pointer(&fields.role) {
+            Some(Value::String(role)) => role,
+            Some(_) => return Err(format!("role at {} is not a string", fields.role)),
+            None => return Err(format!("no role at {}", fields.role)),
+        };
+        let role = self.mapping.roles.get(role).unwrap_or(role).clone();
+
+        let text = match value.pointer(&fields.text) {
+            Some(Value::Null) | None if role == "user" || role == "assistant" => {
+                return Err(format!("no text at {}", fields.text))
+            }
+            Some(text) => Some(Value::String(value_text(text))),
+            None => None,
+        };
+
+        let timestamp = match pointer(value, &fields.timestamp) {
+            None => None,
+            Some(Value::String(s)) => match chrono::DateTime::parse_from_rfc3339(s) {
+                Ok(_) => Some(s.clone()),
+                Err(_) => return Err(format!("timestamp \"{}\" is not RFC 3339", s)),
+            },
+            Some(Value::Number(n)) => {
+                let n = n
+                    .as_i64()
+                    .ok_or_else(|| format!("timestamp {} is not an integer", n))?;
+                // Anything past 2286 in seconds is taken as milliseconds.
+                let millis = if n.unsigned_abs() >= 10_000_000_000 {
+                    n
+                } else {
+                    n * 1000
+                };
+                chrono::DateTime::from_timestamp_millis(millis).map(|t| t.to_rfc3339())
+            }
+            Some(other) => return Err(format!("timestamp {} is not a string or number", other)),
+        };
+
+        let count = |name: &str, path: &Option<String>| match pointer(value, path) {
+            None => Ok(None),
+            Some(v) => v
+                .as_u64()
+                .map(Some)
+                .ok_or_else(|| format!("{} {} is not a token count", name, v)),
+        };
+
+        let input_tokens = count("input_tokens", &fields.input_tokens)?;
+        let output_tokens = count("output_tokens", &fields.output_tokens)?;
+        let cache_read_input_tokens = count("cache_read_tokens", &fields.cache_read_tokens)?;
+        let cache_creation_input_tokens = count("cache_write_tokens", &fields.cache_write_tokens)?;
+        let cost = match pointer(value, &fields.cost) {
+            None => None,
+            Some(v) => Some(
+                v.as_f64()
+                    .ok_or_else(|| format!("cost {} is not a number", v))?,
+            ),
+        };
+
+        let has_usage = input_tokens.is_some()
+            || output_tokens.is_some()
+            || cache_read_input_tokens.is_some()
+            || cache_creation_input_tokens.is_some()
+            || cost.is_some();
+
+        Ok(Message {
+            msg_type: Some(role.clone()),
+            uuid: None,
+            parent_uuid: None,
+            timestamp,
+            session_id: self.line_session_id(value),
+            is_sidechain: false,
+            tool_calls: vec![],
+            message: Some(MessageContent {
+                role: Some(role),
+                content: text,
+                model: pointer(value, &fields.model)
+                    .and_then(|m| m.as_str())
+                    .map(String::from),
+                usage: has_usage.then_some(TokenUsage {
+                    input_tokens,
+                    output_tokens,
+                    cache_read_input_tokens,
+                    cache_creation_input_tokens,
+                    reasoning_tokens: None,
+                    cost,
+                }),
+            }),
+        })
+    }
+}
+
+impl SessionSource for CustomSource {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn root(&self) -> &Path {
+        &self.mapping.root
+    }
+
+    fn list_projects(&self) -> Result<Vec<Project>, String> {
+        if !self.mapping.root.exists() {
+            return Ok(vec![]);
+        }
+
+        let session_count = self
+            .list_session_paths(&self.mapping.root)
+            .map(|s| s.len())
+            .unwrap_or(0);
+
+        if session_count == 0 {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![Project {
+            name: self.mapping.name.clone(),
+            path: self.mapping.root.to_string_lossy().to_string(),
+            session_count,
+        }])
+    }
+
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String> {
+        if !project_path.exists() {
+            return Err("Project path does not exist".to_string());
+        }
+
+        let files = list_jsonl_files(project_path)?;
+        if self.mapping.fields.session_id.is_none() {
+            return Ok(files);
+        }
+
+        let mut sessions = vec![];
+        for file in files {
+            let mut ids: Vec<String> = vec![];
+            for (_, value) in self.session_lines(&file)? {
+                if let Some(id) = value.ok().and_then(|v| self.line_session_id(&v)) {
+                    if !id.is_empty() && !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                }
+            }
+            sessions.extend(ids.iter().map(|id| file.join(encode_session_id(id))));
+        }
+
+        Ok(sessions)
+    }
+
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String> {
+        let messages: Vec<Message> = self
+            .session_lines(session_path)?
+            .into_iter()
+            .filter_map(|(_, value)| self.message(&value.ok()?).ok())
+            .collect();
+
+        Ok(Box::new(messages.into_iter()))
+    }
+
+    fn line_errors(&self, session_path: &Path) -> Result<Vec<LineError>, String> {
+        Ok(self
+            .session_lines(session_path)?
+            .into_iter()
+            .filter_map(|(line, value)| {
+                let message = match value {
+                    Ok(value) => self.message(&value).err()?,
+                    Err(e) => e,
+                };
+                Some(LineError { line, message })
+            })
+            .collect())
+    }
+
+    fn stamp(&self, session_path: &Path) -> Option<SessionStamp> {
+        SessionStamp::of_file(self.session_file(session_path))
+    }
+}
+
+fn pointer<'a>(value: &'a Value, path: &Option<String>) -> Option<&'a Value> {
+    value.pointer(path.as_deref()?).filter(|v| !v.is_null())
+}
+
+/// A session ID as a path component: `%`, `/` and `\\` are percent-encoded,
+/// as are the dots of `.` and `..`.
+fn encode_session_id(id: &str) -> String {
+    if id == "." || id == ".." {
+        return id.replace('.', "%2E");
+    }
+
+    let mut encoded = String::with_capacity(id.len());
+    for c in id.chars() {
+        match c {
+            '%' => encoded.push_str("%25"),
+            '/' => encoded.push_str("%2F"),
+            '\\' => encoded.push_str("%5C"),
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+fn decode_session_id(encoded: &str) -> String {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -0,0 +1,430 @@
+use super::{project_display_name, Records, SessionSource};
+use crate::models::{value_text, Message, MessageContent, Project, TokenUsage, ToolCall};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How Gemini CLI starts the history it sends, with the date, OS and
+/// directory listing.
+const SETUP_PROMPT: &str = "This is the Gemini CLI. We are setting up the context for our chat.";
+
+/// A session recorded by Gemini CLI, `chats/session-*.json`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatFile {
+    session_id: Option<String>,
+    #[serde(default)]
+    messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatMessage {
+    id: Option<String>,
+    timestamp: Option<String>,
+    /// `user`, `gemini`, `info`, `warning` or `error`.
+    #[serde(rename = "type")]
+    kind: String,
+    /// A string, or a list of parts.
+    #[serde(default)]
+    content: Value,
+    #[serde(default)]
+    thoughts: Vec<ChatThought>,
+    #[serde(default)]
+    tool_calls: Vec<ChatToolCall>,
+    tokens: Option<ChatTokens>,
+    model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatThought {
+    #[serde(default)]
+    subject: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatToolCall {
+    id: String,
+    name: String,
+    #[serde(default)]
+    args: Value,
+    /// The `functionResponse` parts sent back to the model.
+    #[serde(default)]
+    result: Value,
+    /// `success`, `error` or `cancelled`.
+    status: Option<String>,
+    /// What the CLI showed for the result, when it is text.
+    result_display: Option<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ChatTokens {
+    input: u64,
+    output: u64,
+    cached: u64,
+    thoughts: u64,
+}
+
+/// One turn of a checkpoint, in the Gemini API's `Content` format.
+#[derive(Debug, Deserialize)]
+struct CheckpointContent {
+    /// `user` or `model`.
+    role: String,
+    #[serde(default)]
+    parts: Vec<Value>,
+}
+
+/// Gemini CLI's per-project directories, `~/.gemini/tmp/<project_hash>`.
+///
+/// Sessions are the chats Gemini CLI records in `chats/session-*.json`, and
+/// the conversations saved with `/chat save <tag>` as `checkpoint-<tag>.json`.
+/// The hash is of the project's path, so projects are named after the path
+/// only when a `.project_root` file records it.
+pub struct GeminiSource {
+    root: PathBuf,
+}
+
+impl GeminiSource {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The project's path, from its `.project_root` file.
+    fn project_root(&self, project_dir: &Path) -> Option<PathBuf> {
+        let root = fs::read_to_string(project_dir.join(".project_root")).ok()?;
+        let root = root.trim();
+        (!root.trim_matches('/').is_empty()).then(|| PathBuf::from(root))
+    }
+
+    fn project_name(&self, project_dir: &Path) -> String {
+        match self.project_root(project_dir) {
+            Some(root) => format!("{} (Gemini)", project_display_name(&root)),
+            None => {
+                let hash = project_dir
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("");
+                format!("Gemini {}", hash.chars().take(8).collect::<String>())
+            }
+        }
+    }
+}
+
+impl SessionSource for GeminiSource {
+    fn id(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn list_projects(&self) -> Result<Vec<Project>, String> {
+        if !self.root.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut projects = vec![];
+
+        let entries = fs::read_dir(&self.root).map_err(|e| e.to_string())?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            let session_count = self.list_session_paths(&path).map(|s| s.len()).unwrap_or(0);
+
+            if session_count > 0 {
+                projects.push(Project {
+                    name: self.project_name(&path),
+                    path: path.to_string_lossy().to_string(),
+                    session_count,
+                });
+            }
+        }
+
+        Ok(projects)
+    }
+
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String> {
+        if !project_path.exists() {
+            return Err("Project path does not exist".to_string());
+        }
+
+        let checkpoints = fs::read_dir(project_path)
+            .map_err(|e| e.to_string())?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| is_session_file(p, "checkpoint-"));
+
+        let chats = fs::read_dir(project_path.join("chats"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| is_session_file(p, "session-"));
+
+        Ok(checkpoints.chain(chats).collect())
+    }
+
+    /// The tag a checkpoint was saved under.
+    fn session_title(&self, session_path: &Path) -> Option<String> {
+        let stem = session_path.file_stem()?.to_str()?;
+        stem.strip_prefix("checkpoint-").map(String::from)
+    }
+
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String> {
+        let content = fs::read_to_string(session_path).map_err(|e| e.to_string())?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse Gemini session: {}", e))?;
+
+        let messages = if value.get("messages").is_some() {
+            let chat = ChatFile::deserialize(&value)
+                .map_err(|e| format!("Failed to parse Gemini session: {}", e))?;
+            chat_messages(chat)
+        } else {
+            // Checkpoints are the bare history, or `{ history, ... }` in
+            // newer versions.
+            let history = value.get("history").unwrap_or(&value);
+            let turns = Vec::<CheckpointContent>::deserialize(history)
+                .map_err(|e| format!("Failed to parse Gemini checkpoint: {}", e))?;
+            checkpoint_messages(turns)
+        };
+
+        Ok(Box::new(messages.into_iter()))
+    }
+
+    fn working_dir(&self, session_path: &Path) -> Option<PathBuf> {
+        session_path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| *dir != self.root)
+            .find_map(|dir| self.project_root(dir))
+    }
+}
+
+/// `<name>#<n>` for the n-th call of, or response from, a tool.
+fn fallback_id(counts: &mut HashMap<String, usize>, name: &str) -> String {
+    let count = counts.entry(name.to_string()).or_default();
+    *count += 1;
+    format!("{}#{}", name, count)
+}
+
+fn is_session_file(path: &Path, prefix: &str) -> bool {
+    path.extension().map(|e| e == "json").unwrap_or(false)
+        && path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with(prefix))
+}
+
+fn chat_messages(chat: ChatFile) -> Vec<Message> {
+    chat.messages
+        .into_iter()
+        .filter_map(|msg| {
+            let role = match msg.kind.as_str() {
+                "user" => "user",
+                "gemini" => "assistant",
+                _ => return None,
+            };
+
+            let mut blocks: Vec<Value> = msg
+                .thoughts
+                .iter()
+                .map(|t| {
+                    let thinking = match (t.subject.is_empty(), t.description.is_empty()) {
+                        (false, false) => format!("{}: {}", t.subject, t.description),
+                        (false, true) => t.subject.clone(),
+                        _ => t.description.clone(),
+                    };
+                    json!({ "type": "thinking", "thinking": thinking })
+                })
+                .collect();
+
+            let text = match &msg.content {
+                Value::String(text) => text.clone(),
+                Value::Array(parts) => parts
+                    .iter()
+                    .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                    .collect::<Vec<_>>()
+                    .join(""),
+                _ => String::new(),
+            };
+            if !text.is_empty() {
+                blocks.push(json!({ "type": "text", "text": text }));
+            }
+
+            Some(Message {
+                msg_type: Some(role.to_string()),
+                uuid: msg.id,
+                parent_uuid: None,
+                timestamp: msg.timestamp,
+                session_id: chat.session_id.clone(),
+                is_sidechain: false,
+                tool_calls: msg.tool_calls.into_iter().map(tool_call).collect(),
+                message: Some(MessageContent {
+                    role: Some(role.to_string()),
+                    content: Some(Value::Array(blocks)),
+                    model: msg.model,
+                    usage: msg.tokens.map(token_usage),
+                }),
+            })
+        })
+        .collect()
+}
+
+fn tool_call(call: ChatToolCall) -> ToolCall {
+    let output = match call.result_display {
+        Some(Value::String(display)) => Some(display),
+        _ => function_response_output(&call.result),
+    };
+
+    ToolCall {
+        id: call.id,
+        name: call.name,
+        input: call.args,
+        output,
+        is_error: call.status.as_deref() == Some("error"),
+        duration_ms: None,
+    }
+}
+
+/// Output or error of the `functionResponse` parts of a tool result.
+fn function_response_output(result: &Value) -> Option<String> {
+    let parts = match result {
+        Value::Array(parts) => parts.iter().collect(),
+        Value::Null => vec![],
+        other => vec![other],
+    };
+
+    let texts: Vec<String> = parts
+        .into_iter()
+        .filter_map(|part| {
+            let response = part.get("functionResponse")?.get("response")?;
+            let output = response.get("output").or_else(|| response.get("error"));
+            Some(
+                output
+                    .map(value_text)
+                    .unwrap_or_else(|| response.to_string()),
+            )
+        })
+        .collect();
+
+    (!texts.is_empty()).then(|| texts.join("\n"))
+}
+
+/// Gemini counts cached tokens as part of the input; they are split out here
+/// as Claude's are.
+fn token_usage(tokens: ChatTokens) -> TokenUsage {
+    TokenUsage {
+        input_tokens: Some(tokens.input.saturating_sub(tokens.cached)),
+        output_tokens: Some(tokens.output),
+        cache_read_input_tokens: Some(tokens.cached),
+        cache_creation_input_tokens: None,
+        reasoning_tokens: Some(tokens.thoughts),
+        cost: None,
+    }
+}
+
+/// Converts checkpoint turns to messages with Claude-style content blocks.
+/// Calls and responses only carry an ID in newer versions, so the others are
+/// paired by tool name, in order.
+fn checkpoint_messages(turns: Vec<CheckpointContent>) -> Vec<Message> {
+    let mut calls: HashMap<String, usize> = HashMap::new();
+    let mut responses: HashMap<String, usize> = HashMap::new();
+
+    // Leave out the setup exchange that starts the history, and the reply
+    // acknowledging it.
+    let is_setup = turns.first().is_some_and(|turn| {
+        turn.parts
+            .first()
+            .and_then(|p| p.get("text"))
+            .and_then(|t| t.as_str())
+            .is_some_and(|text| text.starts_with(SETUP_PROMPT))
+    });
+
+    turns
+        .into_iter()
+        .skip(if is_setup { 2 } else { 0 })
+        .filter_map(|turn| {
+            let role = match turn.role.as_str() {
+                "user" => "user",
+                "model" => "assistant",
+                _ => return None,
+            };
+
+            let blocks: Vec<Value> = turn
+                .parts
+                .iter()
+                .filter_map(|part| {
+                    if let Some(call) = part.get("functionCall") {
+                        let name = call.get("name")?.as_str()?;
+                        let id = match call.get("id").and_then(|i| i.as_str()) {
+                            Some(id) => id.to_string(),
+                            None => fallback_id(&mut calls, name),
+                        };
+                        return Some(json!({
+                            "type": "tool_use",
+                            "id": id,
+                            "name": name,
+                            "input": call.get("args").cloned().unwrap_or_default(),
+                        }));
+                    }
+
+                    if let Some(response) = part.get("functionResponse") {
+                        let name = response.get("name")?.as_str()?;
+                        let id = match response.get("id").and_then(|i| i.as_str()) {
+                            Some(id) => id.to_string(),
+                            None => fallback_id(&mut responses, name),
+                        };
+                        let output = function_response_output(&json!([part]));
+                        let is_error = response
+                            .get("response")
+                            .is_some_and(|r| r.get("error").is_some());
+                        return Some(json!({
+                            "type": "tool_result",
+                            "tool_use_id": id,
+                            "content": output.unwrap_or_default(),
+                            "is_error": is_error,
+                        }));
+                    }
+
+                    let text = part.get("text")?.as_str()?;
+                    if part.get("thought").and_then(|t| t.as_bool()) == Some(true) {
+                        Some(json!({ "type": "thinking", "thinking": text }))
+                    } else {
+                        Some(json!({ "type": "text", "text": text }))
+                    }
+                })
+                .collect();
+
+            if blocks.is_empty() {
+                return None;
+            }
+
+            Some(Message {
+                msg_type: Some(role.to_string()),
+                uuid: None,
+                parent_uuid: None,
+                timestamp: None,
+                session_id: None,
+                is_sidechain: false,
+                tool_calls: vec![],
+                message: Some(MessageContent {
+                    role: Some(role.to_string()),
+                    content: Some(Value::Array(blocks)),
+                    model: None,
+                    usage: None,
+                }),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,517 @@
+mod aider;
+mod claude_projects;
+mod claude_transcripts;
+mod cline;
+mod codex;
+mod custom;
+mod gemini;
+mod opencode;
+mod opencode_export;
+
+pub use aider::AiderSource;
+pub use claude_projects::{decode_dashed_dir, ClaudeProjectsSource};
+pub use claude_transcripts::ClaudeTranscriptsSource;
+pub use cline::ClineSource;
+pub use codex::CodexSource;
+pub use custom::{CustomSource, FieldPaths, Mapping};
+pub use gemini::GeminiSource;
+pub use opencode::OpenCodeStorageSource;
+pub use opencode_export::{import_export, OpenCodeError, OpenCodeExportsSource};
+
+use crate::config::Config;
+use crate::models::{
+    has_text_content, value_text, Message, Project, Session, ToolCall, UsageTotals,
+};
+use crate::record::{ContentBlock, Record};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+pub type Records<'a> = Box<dyn Iterator<Item = Message> + 'a>;
+
+/// A location on disk that some agent writes its conversation history to.
+///
+/// Every Tauri command goes through this trait, so adding support for a new
+/// agent means implementing it and registering the source in
+/// [`SourceRegistry::discover`].
+pub trait SessionSource: Send + Sync {
+    /// Stable identifier, e.g. `claude-projects`.
+    fn id(&self) -> &str;
+
+    /// Directory that every project and session path of this source lives under.
+    fn root(&self) -> &Path;
+
+    fn list_projects(&self) -> Result<Vec<Project>, String>;
+
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String>;
+
+    /// Human-readable name of the session, when the agent stores one.
+    fn session_title(&self, _session_path: &Path) -> Option<String> {
+        None
+    }
+
+    /// Every record stored for the session, including ones the viewer hides.
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String>;
+
+    /// Changes whenever the session's records may have changed; the index
+    /// re-reads a session when this differs from what it saw last time.
+    fn stamp(&self, session_path: &Path) -> Option<SessionStamp> {
+        SessionStamp::of_file(session_path)
+    }
+
+    fn list_sessions(&self, project_path: &Path) -> Result<Vec<Session>, String> {
+        let mut sessions = vec![];
+
+        for session_path in self.list_session_paths(project_path)? {
+            if let Some(session) = build_session(self, &session_path)? {
+                sessions.push(session);
+            }
+        }
+
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.modified));
+
+        Ok(sessions)
+    }
+
+    /// Conversation messages shown in the viewer, with their tool calls.
+    /// Subagent sidechains are shown separately, see [`crate::subagents`].
+    fn load_messages(&self, session_path: &Path) -> Result<Vec<Message>, String> {
+        let mut records: Vec<Message> = self
+            .records(session_path)?
+            .filter(|msg| !msg.is_sidechain)
+            .collect();
+        attach_tool_calls(&mut records);
+
+        Ok(records.into_iter().filter(is_visible).collect())
+    }
+
+    /// Every record of the session's own conversation, typed by kind.
+    /// Sources that keep more than the conversation override this.
+    fn load_records(&self, session_path: &Path) -> Result<Vec<Record>, String> {
+        Ok(self
+            .records(session_path)?
+            .filter(|msg| !msg.is_sidechain)
+            .map(Record::from)
+            .collect())
+    }
+
+    /// Directory the agent worked in during the session, when it is known.
+    fn working_dir(&self, _session_path: &Path) -> Option<PathBuf> {
+        None
+    }
+
+    /// Lines of the session that could not be read as a record, and why.
+    /// Sources that skip unreadable lines silently leave this empty.
+    fn line_errors(&self, _session_path: &Path) -> Result<Vec<LineError>, String> {
+        Ok(vec![])
+    }
+
+    fn owns(&self, path: &Path) -> bool {
+        path.starts_with(self.root())
+    }
+}
+
+/// A line of a session file that could not be read, numbered from 1.
+#[derive(Debug, Serialize)]
+pub struct LineError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// All session sources known to the app, in registration order.
+#[derive(Default)]
+pub struct SourceRegistry {
+    sources: Vec<Box<dyn SessionSource>>,
+    opencode: Option<OpenCodeStorageSource>,
+}
+
+impl SourceRegistry {
+    /// Registers the built-in sources rooted in the user's home directory,
+    /// configured by [`Config::load`].
+    pub fn discover() -> Self {
+        let mut registry = Self::default();
+        let config = Config::load();
+
+        let Some(home) = dirs::home_dir() else {
+            return registry;
+        };
+
+        let claude_dir = home.join(".claude");
+        let opencode_storage = OpenCodeStorageSource::new(
+            home.join(".local/share/opencode/storage"),
+            config.opencode.clone(),
+        );
+
+        registry.register(ClaudeProjectsSource::new(claude_dir.join("projects")));
+        registry.register(ClaudeTranscriptsSource::new(
+            claude_dir.join("transcripts"),
+            opencode_storage.clone(),
+        ));
+        if let Some(export_dir) = config.opencode.export_dir() {
+            registry.register(OpenCodeExportsSource::new(
+                export_dir,
+                opencode_storage.clone(),
+            ));
+        }
+        registry.opencode = Some(opencode_storage.clone());
+        registry.register(opencode_storage);
+
+        // Codex keeps its data in `$CODEX_HOME` when that is set.
+        let codex_dir = std::env::var_os("CODEX_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".codex"));
+        registry.register(CodexSource::new(codex_dir.join("sessions")));
+        registry.register(GeminiSource::new(home.join(".gemini/tmp")));
+
+        if let Some(tasks_dir) = config.cline.tasks_dir("saoudrizwan.claude-dev") {
+            registry.register(ClineSource::cline(tasks_dir));
+        }
+        if let Some(tasks_dir) = config.roo_code.tasks_dir("rooveterinaryinc.roo-cline") {
+            registry.register(ClineSource::roo_code(tasks_dir));
+        }
+
+        for mapping_path in &config.custom {
+            match Mapping::load(mapping_path) {
+                Ok(mapping) => {
+                    let source = CustomSource::new(mapping);
+                    if registry.get(source.id()).is_some() {
+                        eprintln!(
+                            "Ignoring mapping {}: {} is already registered",
+                            mapping_path.display(),
+                            source.id()
+                        );
+                    } else {
+                        registry.register(source);
+                    }
+                }
+                Err(e) => eprintln!("Ignoring mapping {}: {}", mapping_path.display(), e),
+            }
+        }
+
+        for repo in config.aider.repos {
+            let source = AiderSource::new(repo);
+            if registry.get(source.id()).is_some() {
+                eprintln!(
+                    "Ignoring Aider repository {}: {} is already registered",
+                    source.root().display(),
+                    source.id()
+                );
+            } else {
+                registry.register(source);
+            }
+        }
+
+        registry
+    }
+
+    /// The OpenCode storage source, for reading sessions by ID.
+    pub fn opencode(&self) -> Option<&OpenCodeStorageSource> {
+        self.opencode.as_ref()
+    }
+
+    pub fn register(&mut self, source: impl SessionSource + 'static) {
+        self.sources.push(Box::new(source));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn SessionSource> {
+        self.sources.iter().map(|s| s.as_ref())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn SessionSource> {
+        self.iter().find(|s| s.id() == id)
+    }
+
+    /// Finds the source a project or session path belongs to.
+    pub fn source_for(&self, path: &Path) -> Result<&dyn SessionSource, String> {
+        self.iter()
+            .find(|s| s.owns(path))
+            .ok_or_else(|| format!("No session source for path: {}", path.display()))
+    }
+
+    /// The project at `path` and the source it belongs to.
+    pub fn project_at(&self, path: &Path) -> Result<(&dyn SessionSource, Project), String> {
+        let source = self.source_for(path)?;
+        let project = source
+            .list_projects()?
+            .into_iter()
+            .find(|p| Path::new(&p.path) == path)
+            .ok_or("Project path does not exist")?;
+
+        Ok((source, project))
+    }
+
+    /// Projects of every source. A source that cannot be read is reported
+    /// and left out rather than failing the others.
+    pub fn list_projects(&self) -> Result<Vec<Project>, String> {
+        let mut projects = vec![];
+
+        for source in self.iter() {
+            match source.list_projects() {
+                Ok(source_projects) => projects.extend(source_projects),
+                Err(e) => eprintln!("Skipping {} projects: {}", source.id(), e),
+            }
+        }
+
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(projects)
+    }
+
+    /// Directory the agent worked in during a session, as recorded by the
+    /// source the session belongs to.
+    pub fn working_dir(&self, session_path: &Path) -> Option<PathBuf> {
+        self.source_for(session_path)
+            .ok()?
+            .working_dir(session_path)
+    }
+
+    /// Looks a project up by its display name or path.
+    pub fn find_project(&self, name_or_path: &str) -> Result<Project, String> {
+        self.list_projects()?
+            .into_iter()
+            .find(|p| p.name == name_or_path || p.path == name_or_path)
+            .ok_or_else(|| format!("Project not found: {}", name_or_path))
+    }
+
+    /// Looks a session up by its path, or by ID across every project.
+    pub fn find_session(&self, id_or_path: &str) -> Result<PathBuf, String> {
+        let path = PathBuf::from(id_or_path);
+        if path.exists() && self.source_for(&path).is_ok() {
+            return Ok(path);
+        }
+
+        for source in self.iter() {
+            let projects = match source.list_projects() {
+                Ok(projects) => projects,
+                Err(e) => {
+                    eprintln!("Skipping {} projects: {}", source.id(), e);
+                    continue;
+                }
+            };
+
+            for project in projects {
+                let session_paths = match source.list_session_paths(Path::new(&project.path)) {
+                    Ok(paths) => paths,
+                    Err(_) => continue,
+                };
+
+                if let Some(found) = session_paths
+                    .into_iter()
+                    .find(|p| p.file_stem().and_then(|n| n.to_str()) == Some(id_or_path))
+                {
+                    return Ok(found);
+                }
+            }
+        }
+
+        Err(format!("Session not found: {}", id_or_path))
+    }
+}
+
+/// Total size and latest modification time of the files a session is read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionStamp {
+    pub size: u64,
+    /// Milliseconds since the Unix epoch.
+    pub mtime_ms: i64,
+}
+
+impl SessionStamp {
+    pub fn of_file(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let mtime_ms = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+
+        Some(Self {
+            size: metadata.len(),
+            mtime_ms,
+        })
+    }
+
+    /// Covers both stamps, e.g. a session spread over several files.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            size: self.size + other.size,
+            mtime_ms: self.mtime_ms.max(other.mtime_ms),
+        }
+    }
+}
+
+pub fn is_displayable(msg: &Message) -> bool {
+    msg.is_conversation() && has_text_content(&msg.message)
+}
+
+/// Whether the viewer shows the message: it has text, or calls tools.
+pub fn is_visible(msg: &Message) -> bool {
+    is_displayable(msg) || !msg.tool_calls.is_empty()
+}
+
+/// Pairs every `tool_use` block with the `tool_result` answering it, by
+/// `tool_use_id`, and adds the pairs to the `tool_calls` of the message that
+/// made the calls.
+pub fn attach_tool_calls(records: &mut [Message]) {
+    // Output, error flag and timestamp of each result by the call's ID.
+    let mut results: HashMap<String, (String, bool, Option<String>)> = HashMap::new();
+    for msg in records.iter() {
+        for block in msg.blocks() {
+            if let ContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                is_error,
+            } = block
+            {
+                results.insert(
+                    tool_use_id,
+                    (value_text(&content), is_error, msg.timestamp.clone()),
+                );
+            }
+        }
+    }
+
+    for msg in records.iter_mut() {
+        let calls: Vec<ToolCall> = msg
+            .blocks()
+            .into_iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { id, name, input } => {
+                    let result = results.remove(&id);
+                    let duration_ms = result.as_ref().and_then(|(_, _, finished)| {
+                        elapsed_ms(msg.timestamp.as_deref()?, finished.as_deref()?)
+                    });
+                    let (output, is_error) = match result {
+                        Some((output, is_error, _)) => (Some(output), is_error),
+                        None => (None, false),
+                    };
+
+                    Some(ToolCall {
+                        id,
+                        name,
+                        input,
+                        output,
+                        is_error,
+                        duration_ms,
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+
+        msg.tool_calls.extend(calls);
+    }
+}
+
+fn elapsed_ms(start: &str, end: &str) -> Option<i64> {
+    let start = chrono::DateTime::parse_from_rfc3339(start).ok()?;
+    let end = chrono::DateTime::parse_from_rfc3339(end).ok()?;
+    Some((end - start).num_milliseconds().max(0))
+}
+
+pub struct SessionStats {
+    pub usage: UsageTotals,
+    pub message_count: u32,
+}
+
+pub fn calculate_session_stats(records: Records<'_>) -> SessionStats {
+    let mut stats = SessionStats {
+        usage: UsageTotals::default(),
+        message_count: 0,
+    };
+
+    for msg in records {
+        if is_displayable(&msg) {
+            stats.message_count += 1;
+        }
+
+        if let Some(usage) = msg.usage() {
+            stats.usage.add(usage);
+        }
+    }
+
+    stats
+}
+
+fn build_session<S: SessionSource + ?Sized>(
+    source: &S,
+    session_path: &Path,
+) -> Result<Option<Session>, String> {
+    let id = session_path
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_string();
+
+    let stamp = source
+        .stamp(session_path)
+        .ok_or_else(|| format!("Cannot read session: {}", session_path.display()))?;
+
+    let stats = match source.records(session_path) {
+        Ok(records) => calculate_session_stats(records),
+        Err(_) => return Ok(None),
+    };
+
+    if stats.message_count == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(Session {
+        title: source.session_title(session_path),
+        id,
+        path: session_path.to_string_lossy().to_string(),
+        size: stamp.size,
+        modified: (stamp.mtime_ms / 1000).max(0) as u64,
+        input_tokens: stats.usage.input_tokens,
+        output_tokens: stats.usage.output_tokens,
+        reasoning_tokens: stats.usage.reasoning_tokens,
+        cache_read_tokens: stats.usage.cache_read_tokens,
+        cache_write_tokens: stats.usage.cache_write_tokens,
+        cost: stats.usage.cost,
+    }))
+}
+
+/// Name a project is listed under: its directory without the leading `/`.
+pub fn project_display_name(dir: &Path) -> String {
+    dir.to_string_lossy().trim_matches('/').to_string()
+}
+
+/// The first `cwd` field in a JSONL session file, which Claude Code writes on
+/// every conversation record.
+pub(crate) fn jsonl_cwd(path: &Path) -> Option<PathBuf> {
+    read_jsonl_lines(path)
+        .ok()?
+        .filter(|line| line.contains("\"cwd\""))
+        .find_map(|line| {
+            let value: serde_json::Value = serde_json::from_str(&line).ok()?;
+            let cwd = value.get("cwd")?.as_str()?;
+            (!cwd.is_empty()).then(|| PathBuf::from(cwd))
+        })
+}
+
+pub(crate) fn is_jsonl(path: &Path) -> bool {
+    path.extension().map(|ext| ext == "jsonl").unwrap_or(false)
+}
+
+pub(crate) fn list_jsonl_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| e.to_string())?;
+
+    Ok(entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| is_jsonl(p))
+        .collect())
+}
+
+/// Streams the non-empty lines of a JSONL file.
+pub(crate) fn read_jsonl_lines(path: &Path) -> Result<impl Iterator<Item = String>, String> {
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty()))
+}
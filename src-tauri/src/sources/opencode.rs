@@ -0,0 +1,520 @@
+use super::opencode_export::{self, OpenCodeError};
+use super::{Records, SessionSource, SessionStamp};
+use crate::config::OpenCodeConfig;
+use crate::models::{Message, MessageContent, Project, TokenUsage, ToolCall};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Structs for OpenCode storage directory format
+#[derive(Debug, Deserialize)]
+struct StorageMessage {
+    id: String,
+    #[serde(rename = "sessionID")]
+    #[allow(dead_code)]
+    session_id: String,
+    role: String,
+    time: StorageTime,
+    #[serde(rename = "parentID")]
+    parent_id: Option<String>,
+    #[serde(rename = "modelID")]
+    model_id: Option<String>,
+    tokens: Option<StorageTokens>,
+    cost: Option<f64>,
+}
+
+/// Token counts OpenCode keeps on assistant messages, and on the
+/// `step-finish` part of each step of a reply.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(super) struct StorageTokens {
+    input: u64,
+    output: u64,
+    reasoning: u64,
+    cache: StorageCacheTokens,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StorageCacheTokens {
+    read: u64,
+    write: u64,
+}
+
+impl StorageTokens {
+    fn add(&mut self, other: &StorageTokens) {
+        self.input += other.input;
+        self.output += other.output;
+        self.reasoning += other.reasoning;
+        self.cache.read += other.cache.read;
+        self.cache.write += other.cache.write;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct StorageTime {
+    created: i64,
+    #[allow(dead_code)]
+    completed: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StoragePart {
+    id: String,
+    #[serde(rename = "type")]
+    part_type: String,
+    text: Option<String>,
+    #[serde(rename = "callID")]
+    call_id: Option<String>,
+    tool: Option<String>,
+    state: Option<serde_json::Value>,
+    tokens: Option<StorageTokens>,
+    cost: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StorageProject {
+    worktree: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StorageSessionInfo {
+    title: Option<String>,
+    /// Set on sessions OpenCode starts for its own subagents.
+    #[serde(rename = "parentID")]
+    parent_id: Option<String>,
+}
+
+/// OpenCode's storage directory, `~/.local/share/opencode/storage`.
+///
+/// Projects are the directories of `storage/session/<project_id>`, named
+/// after the worktree in `storage/project/<project_id>.json`. Sessions are
+/// addressed by their info file in there, or as `storage/message/<session_id>`;
+/// either way the messages are read from the latter. Once OpenCode has
+/// removed them, a saved export in the configured `export_dir` is read
+/// instead, and failing that the output of `opencode export`.
+#[derive(Clone)]
+pub struct OpenCodeStorageSource {
+    root: PathBuf,
+    config: OpenCodeConfig,
+}
+
+impl OpenCodeStorageSource {
+    pub fn new(root: PathBuf, config: OpenCodeConfig) -> Self {
+        Self { root, config }
+    }
+
+    pub fn session_dir(&self, session_id: &str) -> PathBuf {
+        self.root.join("message").join(session_id)
+    }
+
+    /// Whether the session is listed under one of this source's projects.
+    pub fn lists_session(&self, session_id: &str) -> bool {
+        self.session_info_path(session_id)
+            .and_then(|path| read_session_info(&path))
+            .is_some_and(|info| info.parent_id.is_none())
+    }
+
+    pub fn session_info_path(&self, session_id: &str) -> Option<PathBuf> {
+        fs::read_dir(self.root.join("session"))
+            .ok()?
+            .flatten()
+            .map(|e| e.path().join(format!("{}.json", session_id)))
+            .find(|path| path.is_file())
+    }
+
+    /// The session's messages as storage holds them, or an error when its
+    /// message directory does not exist (yet).
+    pub fn storage_messages(&self, session_id: &str) -> Result<Vec<Message>, String> {
+        read_storage_messages(&self.root, session_id)
+    }
+
+    /// The session's messages, from storage, a saved export or
+    /// `opencode export`, in that order.
+    pub fn load_session(&self, session_id: &str) -> Result<Vec<Message>, OpenCodeError> {
+        if self.session_dir(session_id).exists() {
+            return self
+                .storage_messages(session_id)
+                .map_err(|message| OpenCodeError::Io { message });
+        }
+
+        if let Some(saved) = self
+            .config
+            .export_dir()
+            .map(|dir| dir.join(format!("{}.json", session_id)))
+            .filter(|path| path.is_file())
+        {
+            let export = opencode_export::read_export_file(&saved)?;
+            return Ok(opencode_export::export_messages(export));
+        }
+
+        let export = opencode_export::run_export(
+            self.config.bin.as_deref(),
+            session_id,
+            Duration::from_secs(self.config.export_timeout_secs),
+        )?;
+
+        Ok(opencode_export::export_messages(export))
+    }
+
+    fn worktree(&self, project_id: &str) -> Option<String> {
+        fs::read_to_string(
+            self.root
+                .join("project")
+                .join(format!("{}.json", project_id)),
+        )
+        .ok()
+        .and_then(|content| serde_json::from_str::<StorageProject>(&content).ok())
+        .map(|project| project.worktree)
+    }
+
+    fn project_name(&self, project_id: &str) -> String {
+        match self
+            .worktree(project_id)
+            .as_deref()
+            .map(|w| w.trim_matches('/'))
+        {
+            Some("") => "OpenCode (global)".to_string(),
+            Some(worktree) => format!("{} (OpenCode)", worktree),
+            None => format!("{} (OpenCode)", project_id),
+        }
+    }
+}
+
+impl SessionSource for OpenCodeStorageSource {
+    fn id(&self) -> &'static str {
+        "opencode-storage"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn list_projects(&self) -> Result<Vec<Project>, String> {
+        let session_root = self.root.join("session");
+        if !session_root.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut projects = vec![];
+
+        let entries = fs::read_dir(&session_root).map_err(|e| e.to_string())?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            let project_id = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string();
+
+            let session_count = self.list_session_paths(&path).map(|s| s.len()).unwrap_or(0);
+
+            if session_count > 0 {
+                projects.push(Project {
+                    name: self.project_name(&project_id),
+                    path: path.to_string_lossy().to_string(),
+                    session_count,
+                });
+            }
+        }
+
+        Ok(projects)
+    }
+
+    /// Info files of the project's sessions, leaving out subagent sessions.
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String> {
+        if !project_path.exists() {
+            return Err("Project path does not exist".to_string());
+        }
+
+        let entries = fs::read_dir(project_path).map_err(|e| e.to_string())?;
+
+        Ok(entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+            .filter(|p| read_session_info(p).is_some_and(|info| info.parent_id.is_none()))
+            .collect())
+    }
+
+    fn session_title(&self, session_path: &Path) -> Option<String> {
+        let session_id = session_path.file_stem()?.to_str()?;
+        let info_path = if session_path.is_file() {
+            session_path.to_path_buf()
+        } else {
+            self.session_info_path(session_id)?
+        };
+
+        read_session_info(&info_path)?.title
+    }
+
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String> {
+        let session_id = session_path
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .ok_or("Invalid OpenCode session path")?;
+
+        let messages = self.load_session(session_id).map_err(|e| e.to_string())?;
+
+        Ok(Box::new(messages.into_iter()))
+    }
+
+    /// The worktree of the session's project; none for global sessions.
+    fn working_dir(&self, session_path: &Path) -> Option<PathBuf> {
+        let info_path = if session_path.is_file() {
+            session_path.to_path_buf()
+        } else {
+            self.session_info_path(session_path.file_stem()?.to_str()?)?
+        };
+        let project_id = info_path.parent()?.file_name()?.to_str()?;
+
+        self.worktree(project_id)
+            .filter(|w| !w.trim_matches('/').is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// Covers every message file of the session and the parts of each
+    /// message, which keep being written while a reply streams in, plus the
+    /// info file when the session is addressed by it.
+    fn stamp(&self, session_path: &Path) -> Option<SessionStamp> {
+        let session_id = session_path.file_stem()?.to_str()?;
+        let part_dir = self.root.join("part");
+        let mut stamp = SessionStamp::default();
+
+        if session_path.is_file() {
+            stamp = stamp.merge(SessionStamp::of_file(session_path)?);
+        }
+
+        for entry in fs::read_dir(self.session_dir(session_id)).ok()?.flatten() {
+            let path = entry.path();
+            if let Some(file) = SessionStamp::of_file(&path) {
+                stamp = stamp.merge(file);
+            }
+
+            let Some(message_id) = path.file_stem().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if let Ok(parts) = fs::read_dir(part_dir.join(message_id)) {
+                for part in parts.flatten() {
+                    if let Some(file) = SessionStamp::of_file(&part.path()) {
+                        stamp = stamp.merge(file);
+                    }
+                }
+            }
+        }
+
+        Some(stamp)
+    }
+}
+
+/// Usage summed over the `step-finish` parts of a message, for messages that
+/// do not carry their own totals.
+#[derive(Default)]
+pub(super) struct StepTotals {
+    tokens: Option<StorageTokens>,
+    cost: Option<f64>,
+}
+
+impl StepTotals {
+    pub(super) fn add(&mut self, tokens: Option<StorageTokens>, cost: Option<f64>) {
+        if let Some(tokens) = tokens {
+            self.tokens
+                .get_or_insert_with(StorageTokens::default)
+                .add(&tokens);
+        }
+        if let Some(cost) = cost {
+            self.cost = Some(self.cost.unwrap_or(0.0) + cost);
+        }
+    }
+
+    pub(super) fn usage(self) -> Option<TokenUsage> {
+        Some(token_usage(self.tokens?, self.cost))
+    }
+}
+
+pub(super) fn token_usage(tokens: StorageTokens, cost: Option<f64>) -> TokenUsage {
+    TokenUsage {
+        input_tokens: Some(tokens.input),
+        output_tokens: Some(tokens.output),
+        cache_read_input_tokens: Some(tokens.cache.read),
+        cache_creation_input_tokens: Some(tokens.cache.write),
+        reasoning_tokens: Some(tokens.reasoning),
+        cost,
+    }
+}
+
+fn read_session_info(path: &Path) -> Option<StorageSessionInfo> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn read_storage_messages(storage_dir: &Path, session_id: &str) -> Result<Vec<Message>, String> {
+    let message_dir = storage_dir.join("message").join(session_id);
+    if !message_dir.exists() {
+        return Err(format!(
+            "Message directory not found: {}",
+            message_dir.display()
+        ));
+    }
+
+    let part_dir = storage_dir.join("part");
+
+    let entries = fs::read_dir(&message_dir).map_err(|e| e.to_string())?;
+
+    let mut messages = Vec::new();
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.extension().map(|e| e == "json").unwrap_or(false) {
+            continue;
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let storage_msg: StorageMessage = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse message: {}", e))?;
+
+        if storage_msg.role != "user" && storage_msg.role != "assistant" {
+            continue;
+        }
+
+        let msg_parts_dir = part_dir.join(&storage_msg.id);
+        let mut text_parts = Vec::new();
+        let mut tool_calls = Vec::new();
+        let mut steps = StepTotals::default();
+
+        if let Ok(part_entries) = fs::read_dir(&msg_parts_dir) {
+            let mut parts: Vec<StoragePart> = Vec::new();
+
+            for part_entry in part_entries.flatten() {
+                let part_path = part_entry.path();
+                if !part_path.extension().map(|e| e == "json").unwrap_or(false) {
+                    continue;
+                }
+
+                if let Ok(part_content) = fs::read_to_string(&part_path) {
+                    if let Ok(part) = serde_json::from_str::<StoragePart>(&part_content) {
+                        parts.push(part);
+                    }
+                }
+            }
+
+            parts.sort_by(|a, b| a.id.cmp(&b.id));
+
+            for part in parts {
+                match part.part_type.as_str() {
+                    "text" => {
+                        if let Some(text) = part.text {
+                            text_parts.push(text);
+                        }
+                    }
+                    "reasoning" => {
+                        if let Some(text) = part.text {
+                            text_parts.push(format!("[Reasoning]\n{}", text));
+                        }
+                    }
+                    "tool" => {
+                        if let (Some(call_id), Some(tool)) = (part.call_id, part.tool) {
+                            tool_calls.push(tool_call_from_part(
+                                call_id,
+                                tool,
+                                &part.state.unwrap_or_default(),
+                            ));
+                        }
+                    }
+                    "step-finish" => steps.add(part.tokens, part.cost),
+                    _ => {}
+                }
+            }
+        }
+
+        let content_text = if text_parts.is_empty() {
+            String::new()
+        } else {
+            text_parts.join("\n\n")
+        };
+
+        let created = storage_msg.time.created;
+        let timestamp = chrono::DateTime::from_timestamp_millis(created)
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default();
+
+        messages.push((
+            created,
+            Message {
+                msg_type: Some(storage_msg.role.clone()),
+                uuid: Some(storage_msg.id.clone()),
+                parent_uuid: storage_msg.parent_id,
+                timestamp: Some(timestamp),
+                session_id: Some(session_id.to_string()),
+                is_sidechain: false,
+                tool_calls,
+                message: Some(MessageContent {
+                    role: Some(storage_msg.role),
+                    content: Some(serde_json::Value::String(content_text)),
+                    model: storage_msg.model_id,
+                    usage: match storage_msg.tokens {
+                        Some(tokens) => Some(token_usage(tokens, storage_msg.cost)),
+                        None => steps.usage(),
+                    },
+                }),
+            },
+        ));
+    }
+
+    // `read_dir` order is arbitrary, and steps of one reply can share a
+    // millisecond; IDs are ascending within a session.
+    messages.sort_by(|(a_created, a), (b_created, b)| {
+        a_created.cmp(b_created).then_with(|| a.uuid.cmp(&b.uuid))
+    });
+    let mut messages: Vec<Message> = messages.into_iter().map(|(_, msg)| msg).collect();
+    chain_steps(&mut messages);
+
+    Ok(messages)
+}
+
+/// Every step of a reply points at the prompt it answers. Chains the steps
+/// instead, so they read as one path rather than as sibling branches.
+pub(super) fn chain_steps(messages: &mut [Message]) {
+    let mut previous: Option<(Option<String>, Option<String>)> = None;
+    for msg in messages {
+        let parent_id = msg.parent_uuid.clone();
+        if let Some((previous_id, previous_parent_id)) = previous {
+            if parent_id.is_some() && parent_id == previous_parent_id {
+                msg.parent_uuid = previous_id;
+            }
+        }
+        previous = Some((msg.uuid.clone(), parent_id));
+    }
+}
+
+/// Reads a tool part's `state`, which OpenCode updates in place as the call
+/// goes from `pending` through `running` to `completed` or `error`.
+pub(super) fn tool_call_from_part(
+    call_id: String,
+    tool: String,
+    state: &serde_json::Value,
+) -> ToolCall {
+    let field = |key: &str| state.get(key).and_then(|v| v.as_str()).map(String::from);
+    let is_error = field("status").as_deref() == Some("error");
+    let time = |key: &str| state.get("time")?.get(key)?.as_i64();
+
+    ToolCall {
+        id: call_id,
+        name: tool,
+        input: state.get("input").cloned().unwrap_or_default(),
+        output: if is_error {
+            field("error")
+        } else {
+            field("output")
+        },
+        is_error,
+        duration_ms: time("end")
+            .zip(time("start"))
+            .map(|(end, start)| (end - start).max(0)),
+    }
+}
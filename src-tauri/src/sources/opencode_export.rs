@@ -0,0 +1,454 @@
+use super::opencode::{chain_steps, token_usage, tool_call_from_part, StepTotals, StorageTokens};
+use super::{OpenCodeStorageSource, Records, SessionSource};
+use crate::models::{Message, MessageContent, Project};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Structs for OpenCode export JSON format
+#[derive(Debug, Deserialize)]
+pub(super) struct ExportData {
+    info: Option<ExportSessionInfo>,
+    messages: Vec<ExportMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportSessionInfo {
+    id: Option<String>,
+    title: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportMessage {
+    info: ExportMessageInfo,
+    parts: Vec<ExportMessagePart>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportMessageInfo {
+    id: String,
+    role: String,
+    time: ExportTime,
+    #[serde(rename = "parentID")]
+    parent_id: Option<String>,
+    #[serde(rename = "modelID")]
+    model_id: Option<String>,
+    tokens: Option<StorageTokens>,
+    cost: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportTime {
+    created: i64, // Unix timestamp in milliseconds
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum ExportMessagePart {
+    #[serde(rename = "text")]
+    Text { text: String },
+    #[serde(rename = "reasoning")]
+    Reasoning { text: String },
+    #[serde(rename = "tool")]
+    Tool {
+        #[serde(rename = "callID")]
+        call_id: String,
+        tool: String,
+        state: serde_json::Value,
+    },
+    #[serde(rename = "step-finish")]
+    StepFinish {
+        tokens: Option<StorageTokens>,
+        cost: Option<f64>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Why an OpenCode session could not be read. Serialized with a `kind` tag
+/// like [`crate::search::SearchError`].
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OpenCodeError {
+    /// Neither the configured binary nor `opencode` on `PATH` or in
+    /// `~/.opencode/bin` exists.
+    BinaryNotFound {
+        configured: Option<String>,
+    },
+    Spawn {
+        binary: String,
+        message: String,
+    },
+    /// `opencode export` ran longer than `export_timeout_secs` and was killed.
+    TimedOut {
+        seconds: u64,
+    },
+    /// `opencode export` exited unsuccessfully.
+    Failed {
+        code: Option<i32>,
+        stderr: String,
+    },
+    /// The output or file holds no export JSON.
+    InvalidExport {
+        message: String,
+    },
+    /// Reading the storage directory or an export file failed.
+    Io {
+        message: String,
+    },
+}
+
+impl fmt::Display for OpenCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenCodeError::BinaryNotFound {
+                configured: Some(path),
+            } => write!(f, "OpenCode binary not found at: {}", path),
+            OpenCodeError::BinaryNotFound { configured: None } => {
+                f.write_str("Could not find the opencode binary on PATH")
+            }
+            OpenCodeError::Spawn { binary, message } => {
+                write!(f, "Failed to execute {} export: {}", binary, message)
+            }
+            OpenCodeError::TimedOut { seconds } => {
+                write!(f, "OpenCode export did not finish within {}s", seconds)
+            }
+            OpenCodeError::Failed { code, stderr } => match code {
+                Some(code) => write!(f, "OpenCode export failed ({}): {}", code, stderr.trim()),
+                None => write!(f, "OpenCode export was killed: {}", stderr.trim()),
+            },
+            OpenCodeError::InvalidExport { message } => {
+                write!(f, "Failed to parse export JSON: {}", message)
+            }
+            OpenCodeError::Io { message } => f.write_str(message),
+        }
+    }
+}
+
+/// Saved `opencode export` output, one `<session_id>.json` per session, in
+/// the `opencode.export_dir` of the config. Sessions OpenCode's storage
+/// still lists are left out, as they are read from there.
+pub struct OpenCodeExportsSource {
+    root: PathBuf,
+    storage: OpenCodeStorageSource,
+}
+
+impl OpenCodeExportsSource {
+    pub fn new(root: PathBuf, storage: OpenCodeStorageSource) -> Self {
+        Self { root, storage }
+    }
+}
+
+impl SessionSource for OpenCodeExportsSource {
+    fn id(&self) -> &'static str {
+        "opencode-exports"
+    }
+
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn list_projects(&self) -> Result<Vec<Project>, String> {
+        if !self.root.exists() {
+            return Ok(vec![]);
+        }
+
+        let session_count = self
+            .list_session_paths(&self.root)
+            .map(|s| s.len())
+            .unwrap_or(0);
+
+        if session_count == 0 {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![Project {
+            name: "OpenCode Exports".to_string(),
+            path: self.root.to_string_lossy().to_string(),
+            session_count,
+        }])
+    }
+
+    fn list_session_paths(&self, project_path: &Path) -> Result<Vec<PathBuf>, String> {
+        if !project_path.exists() {
+            return Err("Project path does not exist".to_string());
+        }
+
+        let entries = fs::read_dir(project_path).map_err(|e| e.to_string())?;
+
+        Ok(entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+            .filter(|p| {
+                !p.file_stem()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|id| self.storage.lists_session(id))
+            })
+            .collect())
+    }
+
+    fn session_title(&self, session_path: &Path) -> Option<String> {
+        read_export_file(session_path).ok()?.info?.title
+    }
+
+    fn records(&self, session_path: &Path) -> Result<Records<'_>, String> {
+        let export = read_export_file(session_path).map_err(|e| e.to_string())?;
+
+        Ok(Box::new(export_messages(export).into_iter()))
+    }
+}
+
+/// Reads an export saved to disk, either the bare JSON or the whole output of
+/// `opencode export`.
+pub(super) fn read_export_file(path: &Path) -> Result<ExportData, OpenCodeError> {
+    let content = fs::read_to_string(path).map_err(|e| OpenCodeError::Io {
+        message: format!("Failed to read {}: {}", path.display(), e),
+    })?;
+
+    parse_export_output(&content)
+}
+
+/// Checks that `file` holds an OpenCode export and copies it into
+/// `export_dir` as `<session_id>.json`, where [`OpenCodeExportsSource`]
+/// picks it up. Returns the path it was saved to.
+///
+/// The session ID comes from the file, so it must be a plain token such as
+/// `ses_4f1a…`; anything that could name another directory is rejected.
+pub fn import_export(file: &Path, export_dir: &Path) -> Result<PathBuf, OpenCodeError> {
+    let export = read_export_file(file)?;
+
+    let session_id = export
+        .info
+        .and_then(|info| info.id)
+        .or_else(|| file.file_stem()?.to_str().map(String::from))
+        .ok_or_else(|| OpenCodeError::InvalidExport {
+            message: "Export has no session ID".to_string(),
+        })?;
+
+    let is_plain = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+    if session_id.is_empty() || !session_id.chars().all(is_plain) {
+        return Err(OpenCodeError::InvalidExport {
+            message: format!("Invalid session ID: {:?}", session_id),
+        });
+    }
+
+    let io_error = |e: std::io::Error| OpenCodeError::Io {
+        message: format!("Failed to save export to {}: {}", export_dir.display(), e),
+    };
+
+    fs::create_dir_all(export_dir).map_err(io_error)?;
+    let target = export_dir.join(format!("{}.json", session_id));
+    fs::copy(file, &target).map_err(io_error)?;
+
+    Ok(target)
+}
+
+/// Runs `opencode export <session_id>` and reads its output, killing it
+/// after `timeout`.
+pub(super) fn run_export(
+    configured_bin: Option<&Path>,
+    session_id: &str,
+    timeout: Duration,
+) -> Result<ExportData, OpenCodeError> {
+    let binary = find_binary(configured_bin).ok_or_else(|| OpenCodeError::BinaryNotFound {
+        configured: configured_bin.map(|p| p.display().to_string()),
+    })?;
+
+    let mut child = Command::new(&binary)
+        .arg("export")
+        .arg(session_id)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| OpenCodeError::Spawn {
+            binary: binary.display().to_string(),
+            message: e.to_string(),
+        })?;
+
+    // Drain both pipes while waiting, so a large export cannot fill a pipe and
+    // stall the process until the timeout.
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(OpenCodeError::TimedOut {
+                    seconds: timeout.as_secs(),
+                });
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => {
+                return Err(OpenCodeError::Spawn {
+                    binary: binary.display().to_string(),
+                    message: e.to_string(),
+                })
+            }
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&stdout.join().unwrap_or_default()).into_owned();
+    let stderr = String::from_utf8_lossy(&stderr.join().unwrap_or_default()).into_owned();
+
+    if !status.success() {
+        return Err(OpenCodeError::Failed {
+            code: status.code(),
+            stderr,
+        });
+    }
+
+    parse_export_output(&stdout)
+}
+
+/// The configured binary, or `opencode` on `PATH`, or the one the OpenCode
+/// installer puts in `~/.opencode/bin`.
+fn find_binary(configured: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = configured {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+
+    let name = if cfg!(windows) {
+        "opencode.exe"
+    } else {
+        "opencode"
+    };
+
+    let path_dirs = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    path_dirs
+        .into_iter()
+        .chain(dirs::home_dir().map(|home| home.join(".opencode/bin")))
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+/// `opencode export` can print log lines or a banner before the JSON, and
+/// those may contain braces. Tries each line that opens an object and takes
+/// the first one that parses as an export, ignoring anything after it.
+fn parse_export_output(output: &str) -> Result<ExportData, OpenCodeError> {
+    let line_starts = std::iter::once(0).chain(output.match_indices('\n').map(|(i, _)| i + 1));
+    let mut last_error = None;
+
+    for start in line_starts {
+        let line = output[start..].trim_start_matches([' ', '\t', '\r']);
+        if !line.starts_with('{') {
+            continue;
+        }
+
+        match serde_json::Deserializer::from_str(line)
+            .into_iter::<ExportData>()
+            .next()
+        {
+            Some(Ok(export)) => return Ok(export),
+            Some(Err(e)) => last_error = Some(e.to_string()),
+            None => {}
+        }
+    }
+
+    Err(OpenCodeError::InvalidExport {
+        message: last_error.unwrap_or_else(|| "No JSON found in export output".to_string()),
+    })
+}
+
+pub(super) fn export_messages(export: ExportData) -> Vec<Message> {
+    let session_id = export.info.and_then(|info| info.id);
+
+    let mut messages: Vec<Message> = export
+        .messages
+        .into_iter()
+        .filter_map(convert_export_message_to_message)
+        .map(|mut msg| {
+            msg.session_id = session_id.clone();
+            msg
+        })
+        .collect();
+    chain_steps(&mut messages);
+
+    messages
+}
+
+fn convert_export_message_to_message(export_msg: ExportMessage) -> Option<Message> {
+    let role = export_msg.info.role.clone();
+
+    if role != "user" && role != "assistant" {
+        return None;
+    }
+
+    let mut text_parts = Vec::new();
+    let mut tool_calls = Vec::new();
+    let mut steps = StepTotals::default();
+
+    for part in export_msg.parts {
+        match part {
+            ExportMessagePart::Text { text } => {
+                text_parts.push(text);
+            }
+            ExportMessagePart::Reasoning { text } => {
+                text_parts.push(format!("[Reasoning]\n{}", text));
+            }
+            ExportMessagePart::Tool {
+                call_id,
+                tool,
+                state,
+            } => {
+                tool_calls.push(tool_call_from_part(call_id, tool, &state));
+            }
+            ExportMessagePart::StepFinish { tokens, cost } => steps.add(tokens, cost),
+            ExportMessagePart::Other => {}
+        }
+    }
+
+    let usage = match export_msg.info.tokens {
+        Some(tokens) => Some(token_usage(tokens, export_msg.info.cost)),
+        None => steps.usage(),
+    };
+
+    // A step that only finished still carries the step's tokens.
+    if text_parts.is_empty() && tool_calls.is_empty() && usage.is_none() {
+        return None;
+    }
+
+    let content_text = text_parts.join("\n\n");
+    let timestamp = chrono::DateTime::from_timestamp_millis(export_msg.info.time.created)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default();
+
+    Some(Message {
+        msg_type: Some(role.clone()),
+        uuid: Some(export_msg.info.id),
+        parent_uuid: export_msg.info.parent_id,
+        timestamp: Some(timestamp),
+        session_id: None,
+        is_sidechain: false,
+        tool_calls,
+        message: Some(MessageContent {
+            role: Some(role),
+            content: Some(serde_json::Value::String(content_text)),
+            model: export_msg.info.model_id,
+            usage,
+        }),
+    })
+}
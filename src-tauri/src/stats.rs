@@ -0,0 +1,198 @@
+use crate::index::SessionIndex;
+use crate::models::UsageTotals;
+use crate::sources::SourceRegistry;
+use chrono::{Datelike, Timelike};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize)]
+pub struct DailyStats {
+    pub date: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub session_count: u32,
+    pub message_count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HourlyActivity {
+    pub hour: u8,
+    pub day: u8,
+    pub count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectStats {
+    pub name: String,
+    pub path: String,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    pub session_count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DashboardStats {
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    pub total_reasoning_tokens: u64,
+    pub total_cache_read_tokens: u64,
+    pub total_cache_write_tokens: u64,
+    pub total_sessions: u32,
+    pub total_messages: u32,
+    pub daily_stats: Vec<DailyStats>,
+    pub hourly_activity: Vec<HourlyActivity>,
+    pub project_stats: Vec<ProjectStats>,
+    /// Recorded cost where the agent records one, estimated from tokens
+    /// elsewhere.
+    pub estimated_cost: f64,
+    /// The part of `estimated_cost` that was recorded by the agents.
+    pub recorded_cost: f64,
+    pub avg_session_minutes: f64,
+}
+
+pub fn dashboard_stats(
+    registry: &SourceRegistry,
+    index: &SessionIndex,
+) -> Result<DashboardStats, String> {
+    index.sync(registry)?;
+
+    let mut totals = UsageTotals::default();
+    let mut estimated_cost = 0.0;
+    let mut recorded_cost = 0.0;
+    let mut total_sessions = 0u32;
+    let mut total_messages = 0u32;
+    let mut total_session_duration_secs = 0i64;
+    let mut sessions_with_duration = 0u32;
+    let mut daily_map: HashMap<String, DailyStats> = HashMap::new();
+    let mut hourly_map: HashMap<(u8, u8), u32> = HashMap::new();
+    let mut project_map: HashMap<String, ProjectStats> = HashMap::new();
+
+    for session in index.sessions()? {
+        let usage = session.usage;
+        total_sessions += 1;
+        totals.input_tokens += usage.input_tokens;
+        totals.output_tokens += usage.output_tokens;
+        totals.reasoning_tokens += usage.reasoning_tokens;
+        totals.cache_read_tokens += usage.cache_read_tokens;
+        totals.cache_write_tokens += usage.cache_write_tokens;
+
+        match usage.cost {
+            Some(cost) => {
+                estimated_cost += cost;
+                recorded_cost += cost;
+            }
+            None => estimated_cost += estimate_cost(usage.input_tokens, usage.output_tokens),
+        }
+
+        let project = project_map
+            .entry(session.project_path.clone())
+            .or_insert(ProjectStats {
+                name: session.project_name,
+                path: session.project_path,
+                total_input_tokens: 0,
+                total_output_tokens: 0,
+                session_count: 0,
+            });
+        project.total_input_tokens += usage.input_tokens;
+        project.total_output_tokens += usage.output_tokens;
+        project.session_count += 1;
+
+        let first = session
+            .first_timestamp
+            .and_then(|ts| chrono::DateTime::parse_from_rfc3339(&ts).ok());
+        let last = session
+            .last_timestamp
+            .and_then(|ts| chrono::DateTime::parse_from_rfc3339(&ts).ok());
+
+        if let (Some(first), Some(last)) = (first, last) {
+            let duration = last.signed_duration_since(first);
+            if duration.num_seconds() > 0 {
+                total_session_duration_secs += duration.num_seconds();
+                sessions_with_duration += 1;
+            }
+        }
+    }
+
+    for activity in index.message_activity()? {
+        if activity.is_conversation {
+            total_messages += 1;
+        }
+
+        let Some(timestamp) = activity.timestamp.as_deref() else {
+            continue;
+        };
+        let Some(date) = timestamp.split('T').next() else {
+            continue;
+        };
+        let has_usage = activity.input_tokens > 0 || activity.output_tokens > 0;
+
+        if activity.is_conversation || has_usage {
+            let entry = daily_map.entry(date.to_string()).or_insert(DailyStats {
+                date: date.to_string(),
+                input_tokens: 0,
+                output_tokens: 0,
+                session_count: 0,
+                message_count: 0,
+            });
+            entry.input_tokens += activity.input_tokens;
+            entry.output_tokens += activity.output_tokens;
+
+            if activity.is_conversation {
+                entry.message_count += 1;
+            }
+        }
+
+        if !activity.is_conversation {
+            continue;
+        }
+
+        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(timestamp) {
+            let hour = dt.hour() as u8;
+            let day = dt.weekday().num_days_from_monday() as u8;
+            *hourly_map.entry((hour, day)).or_insert(0) += 1;
+        }
+    }
+
+    let mut project_stats: Vec<ProjectStats> = project_map.into_values().collect();
+
+    let mut daily_stats: Vec<DailyStats> = daily_map.into_values().collect();
+    daily_stats.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let mut hourly_activity: Vec<HourlyActivity> = hourly_map
+        .into_iter()
+        .map(|((hour, day), count)| HourlyActivity { hour, day, count })
+        .collect();
+    hourly_activity.sort_by_key(|a| (a.day, a.hour));
+
+    project_stats.sort_by(|a, b| {
+        (b.total_input_tokens + b.total_output_tokens)
+            .cmp(&(a.total_input_tokens + a.total_output_tokens))
+    });
+
+    let avg_session_minutes = if sessions_with_duration > 0 {
+        (total_session_duration_secs as f64 / sessions_with_duration as f64) / 60.0
+    } else {
+        0.0
+    };
+
+    Ok(DashboardStats {
+        total_input_tokens: totals.input_tokens,
+        total_output_tokens: totals.output_tokens,
+        total_reasoning_tokens: totals.reasoning_tokens,
+        total_cache_read_tokens: totals.cache_read_tokens,
+        total_cache_write_tokens: totals.cache_write_tokens,
+        total_sessions,
+        total_messages,
+        daily_stats,
+        hourly_activity,
+        project_stats,
+        estimated_cost,
+        recorded_cost,
+        avg_session_minutes,
+    })
+}
+
+/// Rough cost at Claude Sonnet prices, for sessions without a recorded cost.
+fn estimate_cost(input_tokens: u64, output_tokens: u64) -> f64 {
+    (input_tokens as f64 * 0.003 / 1000.0) + (output_tokens as f64 * 0.015 / 1000.0)
+}
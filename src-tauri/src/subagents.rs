@@ -0,0 +1,161 @@
+use crate::models::{extract_text_content, Message};
+use crate::sources::{attach_tool_calls, is_visible, SourceRegistry};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Tools that start a subagent.
+const SPAWNING_TOOLS: [&str; 2] = ["Task", "Agent"];
+
+/// The conversation of one subagent, grouped under the tool call that
+/// spawned it.
+#[derive(Debug, Serialize)]
+pub struct Subagent {
+    /// `id` of the spawning `tool_use` block, when it could be matched.
+    pub tool_use_id: Option<String>,
+    /// UUID of the record holding the spawning call.
+    pub message_uuid: Option<String>,
+    /// Nearest main conversation message the viewer shows at or before the
+    /// spawning call; the subagent is rendered below it.
+    pub anchor_uuid: Option<String>,
+    pub description: Option<String>,
+    pub subagent_type: Option<String>,
+    /// Shown messages, starting with the prompt it was given.
+    pub messages: Vec<Message>,
+}
+
+struct TaskCall {
+    tool_use_id: String,
+    message_uuid: Option<String>,
+    prompt: String,
+    description: Option<String>,
+    subagent_type: Option<String>,
+}
+
+/// Groups a session's sidechain records into one conversation per subagent.
+///
+/// Sidechain records do not name the call that spawned them, so each chain is
+/// matched to the `Task` call whose prompt its first message repeats.
+pub fn session_subagents(
+    registry: &SourceRegistry,
+    session_path: &Path,
+) -> Result<Vec<Subagent>, String> {
+    let mut calls: Vec<TaskCall> = vec![];
+    // Main conversation records by UUID: parent UUID and whether it is shown.
+    let mut main: HashMap<String, (Option<String>, bool)> = HashMap::new();
+    // Sidechain UUID to the index of its chain in `chains`.
+    let mut chain_of: HashMap<String, usize> = HashMap::new();
+    let mut chains: Vec<Vec<Message>> = vec![];
+
+    let mut records: Vec<Message> = registry
+        .source_for(session_path)?
+        .records(session_path)?
+        .collect();
+    attach_tool_calls(&mut records);
+
+    for msg in records {
+        if !msg.is_sidechain {
+            calls.extend(task_calls(&msg));
+            if let Some(uuid) = &msg.uuid {
+                main.insert(uuid.clone(), (msg.parent_uuid.clone(), is_visible(&msg)));
+            }
+            continue;
+        }
+
+        let chain = match msg.parent_uuid.as_ref().and_then(|p| chain_of.get(p)) {
+            Some(&chain) => chain,
+            None => {
+                chains.push(vec![]);
+                chains.len() - 1
+            }
+        };
+        if let Some(uuid) = &msg.uuid {
+            chain_of.insert(uuid.clone(), chain);
+        }
+        chains[chain].push(msg);
+    }
+
+    let mut matched = vec![false; calls.len()];
+
+    Ok(chains
+        .into_iter()
+        .map(|records| {
+            let prompt = records
+                .iter()
+                .find(|m| m.role() == Some("user"))
+                .map(|m| extract_text_content(&m.message))
+                .unwrap_or_default();
+
+            let call = calls
+                .iter()
+                .enumerate()
+                .find(|(i, call)| !matched[*i] && call.prompt.trim() == prompt.trim())
+                .map(|(i, call)| {
+                    matched[i] = true;
+                    call
+                });
+
+            Subagent {
+                tool_use_id: call.map(|c| c.tool_use_id.clone()),
+                message_uuid: call.and_then(|c| c.message_uuid.clone()),
+                anchor_uuid: call
+                    .and_then(|c| displayed_ancestor(&main, c.message_uuid.as_deref())),
+                description: call.and_then(|c| c.description.clone()),
+                subagent_type: call.and_then(|c| c.subagent_type.clone()),
+                messages: records.into_iter().filter(is_visible).collect(),
+            }
+        })
+        .collect())
+}
+
+fn task_calls(msg: &Message) -> Vec<TaskCall> {
+    let Some(items) = msg
+        .message
+        .as_ref()
+        .and_then(|m| m.content.as_ref())
+        .and_then(|c| c.as_array())
+    else {
+        return vec![];
+    };
+
+    items
+        .iter()
+        .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+        .filter(|item| {
+            item.get("name")
+                .and_then(|n| n.as_str())
+                .is_some_and(|n| SPAWNING_TOOLS.contains(&n))
+        })
+        .filter_map(|item| {
+            let input = item.get("input")?;
+            let field = |key: &str| input.get(key).and_then(|v| v.as_str()).map(String::from);
+
+            Some(TaskCall {
+                tool_use_id: item.get("id")?.as_str()?.to_string(),
+                message_uuid: msg.uuid.clone(),
+                prompt: field("prompt").unwrap_or_default(),
+                description: field("description"),
+                subagent_type: field("subagent_type"),
+            })
+        })
+        .collect()
+}
+
+/// Walks up from `uuid` to the first record the viewer shows.
+fn displayed_ancestor(
+    main: &HashMap<String, (Option<String>, bool)>,
+    uuid: Option<&str>,
+) -> Option<String> {
+    let mut current = uuid?;
+
+    // Bounded in case a malformed file links records in a cycle.
+    for _ in 0..main.len() {
+        let (parent, shown) = main.get(current)?;
+        if *shown {
+            return Some(current.to_string());
+        }
+        current = parent.as_deref()?;
+    }
+
+    None
+}
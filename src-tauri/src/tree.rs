@@ -0,0 +1,165 @@
+use crate::models::Message;
+use crate::sources::{attach_tool_calls, is_visible, SourceRegistry};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The messages of a session arranged by their parent links. Rewinding or
+/// editing a prompt leaves the abandoned attempt in the file as a sibling
+/// branch, which this keeps instead of flattening.
+#[derive(Debug, Serialize)]
+pub struct MessageTree {
+    /// Every message the viewer shows, in file order.
+    pub nodes: Vec<TreeNode>,
+    /// IDs of the nodes without a parent.
+    pub roots: Vec<String>,
+    /// IDs from a root down to the leaf of the active branch.
+    pub active_path: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TreeNode {
+    /// The message UUID, or `#<line>` for records without one.
+    pub id: String,
+    /// Nearest shown ancestor. Tool results and other hidden records in
+    /// between are skipped.
+    pub parent: Option<String>,
+    /// In file order, so the last child is the most recent attempt.
+    pub children: Vec<String>,
+    /// Set when the conversation continued in more than one way from here.
+    pub is_branch_point: bool,
+    pub message: Message,
+}
+
+/// Builds the tree of a session. `active_leaf` picks the active branch by any
+/// node on it; the branch then continues down the most recent children. By
+/// default it ends at the last message written.
+pub fn message_tree(
+    registry: &SourceRegistry,
+    session_path: &Path,
+    active_leaf: Option<&str>,
+) -> Result<MessageTree, String> {
+    // Subagent sidechains are not part of the session's own conversation.
+    let mut records: Vec<Message> = registry
+        .source_for(session_path)?
+        .records(session_path)?
+        .filter(|msg| !msg.is_sidechain)
+        .collect();
+    attach_tool_calls(&mut records);
+
+    // Nearest shown ancestor of every record seen so far, counting a shown
+    // record as its own.
+    let mut shown_ancestor: HashMap<String, Option<String>> = HashMap::new();
+    let mut previous: Option<String> = None;
+    let mut nodes: Vec<TreeNode> = vec![];
+
+    for (seq, msg) in records.into_iter().enumerate() {
+        let id = msg.uuid.clone().unwrap_or_else(|| format!("#{}", seq));
+        if shown_ancestor.contains_key(&id) {
+            continue;
+        }
+
+        // Records whose parent is missing, or that have no parent link at
+        // all, continue from the record before them.
+        let parent = msg
+            .parent_uuid
+            .clone()
+            .filter(|p| shown_ancestor.contains_key(p))
+            .or(previous.take());
+
+        let parent_node = parent.and_then(|p| shown_ancestor.get(&p).cloned().flatten());
+
+        if is_visible(&msg) {
+            shown_ancestor.insert(id.clone(), Some(id.clone()));
+            nodes.push(TreeNode {
+                id: id.clone(),
+                parent: parent_node,
+                children: vec![],
+                is_branch_point: false,
+                message: msg,
+            });
+        } else {
+            shown_ancestor.insert(id.clone(), parent_node);
+        }
+
+        previous = Some(id);
+    }
+
+    let index: HashMap<String, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id.clone(), i))
+        .collect();
+
+    let links: Vec<(usize, String)> = nodes
+        .iter()
+        .filter_map(|n| Some((*index.get(n.parent.as_ref()?)?, n.id.clone())))
+        .collect();
+    for (parent, id) in links {
+        nodes[parent].children.push(id);
+    }
+
+    for node in &mut nodes {
+        node.is_branch_point = node.children.len() > 1;
+    }
+
+    let roots = nodes
+        .iter()
+        .filter(|n| n.parent.is_none())
+        .map(|n| n.id.clone())
+        .collect();
+
+    let active_path = active_path(&nodes, &index, active_leaf)?;
+
+    Ok(MessageTree {
+        nodes,
+        roots,
+        active_path,
+    })
+}
+
+fn active_path(
+    nodes: &[TreeNode],
+    index: &HashMap<String, usize>,
+    active_leaf: Option<&str>,
+) -> Result<Vec<String>, String> {
+    let start = match active_leaf {
+        Some(id) => *index
+            .get(id)
+            .ok_or_else(|| format!("Message not found in session: {}", id))?,
+        None => match nodes.len() {
+            0 => return Ok(vec![]),
+            len => len - 1,
+        },
+    };
+
+    // Position of the most recent message under each node. Children always
+    // come after their parent, so one backwards pass is enough.
+    let mut latest: Vec<usize> = (0..nodes.len()).collect();
+    for i in (0..nodes.len()).rev() {
+        if let Some(&parent) = nodes[i].parent.as_ref().and_then(|p| index.get(p)) {
+            latest[parent] = latest[parent].max(latest[i]);
+        }
+    }
+
+    let mut path = vec![];
+    let mut current = Some(start);
+    while let Some(i) = current {
+        path.push(nodes[i].id.clone());
+        current = nodes[i].parent.as_ref().and_then(|p| index.get(p)).copied();
+    }
+    path.reverse();
+
+    let mut current = start;
+    while let Some(next) = nodes[current]
+        .children
+        .iter()
+        .filter_map(|c| index.get(c).copied())
+        .max_by_key(|&c| latest[c])
+    {
+        path.push(nodes[next].id.clone());
+        current = next;
+    }
+
+    Ok(path)
+}
@@ -0,0 +1,478 @@
+use crate::models::{Message, UsageTotals};
+use crate::sources::{
+    attach_tool_calls, is_visible, list_jsonl_files, OpenCodeStorageSource, SessionSource,
+    SourceRegistry,
+};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// How long changes are collected before they are read, so a reply being
+/// streamed into a file is read once per burst rather than once per write.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Most messages kept waiting for the results of their tool calls; calls
+/// that were interrupted never get one.
+const MAX_PENDING: usize = 32;
+
+/// A change to a watched session, sent to the viewer as the
+/// `session-event` event.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WatchEvent {
+    SessionCreated {
+        project_path: String,
+        session_path: String,
+    },
+    /// Messages written since the last event. A message already sent is sent
+    /// again once its tool calls get their results, so messages replace the
+    /// ones with the same `uuid`.
+    MessagesAppended {
+        session_path: String,
+        messages: Vec<Message>,
+    },
+    TokensChanged {
+        session_path: String,
+        #[serde(flatten)]
+        usage: UsageTotals,
+    },
+}
+
+/// Reads what was appended to a Claude Code session file since the last
+/// read, without reading the file again from the start.
+#[derive(Debug, Default)]
+pub struct SessionTail {
+    /// End of the last complete line read.
+    offset: u64,
+    /// Lines of messages whose tool calls are still waiting for results.
+    pending: Vec<String>,
+    usage: UsageTotals,
+    /// Whether `usage` covers the file up to `offset`.
+    counted: bool,
+}
+
+/// What a [`SessionTail`] read.
+#[derive(Debug, Default)]
+pub struct TailUpdate {
+    pub messages: Vec<Message>,
+    /// Token totals of the whole session, when they changed.
+    pub usage: Option<UsageTotals>,
+}
+
+impl SessionTail {
+    /// Starts at the beginning of a new file.
+    pub fn new() -> Self {
+        Self {
+            counted: true,
+            ..Self::default()
+        }
+    }
+
+    /// Starts at the current end of an existing file. Its tokens so far are
+    /// counted on the first read, so only sessions that change are read in
+    /// full.
+    pub fn at_end(path: &Path) -> Self {
+        Self {
+            offset: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            ..Self::default()
+        }
+    }
+
+    pub fn read(&mut self, path: &Path) -> Result<TailUpdate, String> {
+        let mut file = File::open(path).map_err(|e| e.to_string())?;
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+
+        // Rewritten rather than appended to: start over.
+        if len < self.offset {
+            *self = Self::new();
+        }
+
+        let previous_usage = self.usage;
+
+        if !self.counted {
+            let head = BufReader::new(&mut file).take(self.offset);
+            for line in head.split(b'\n') {
+                let line = line.map_err(|e| e.to_string())?;
+                let Ok(message) = serde_json::from_slice::<Message>(&line) else {
+                    continue;
+                };
+                if let Some(usage) = message.usage().filter(|_| !message.is_sidechain) {
+                    self.usage.add(usage);
+                }
+            }
+            self.counted = true;
+        }
+
+        file.seek(SeekFrom::Start(self.offset))
+            .map_err(|e| e.to_string())?;
+        let mut appended = vec![];
+        file.read_to_end(&mut appended).map_err(|e| e.to_string())?;
+
+        // A line still being written is left for the next read.
+        let Some(end) = appended.iter().rposition(|b| *b == b'\n') else {
+            return Ok(TailUpdate {
+                messages: vec![],
+                usage: (self.usage != previous_usage).then_some(self.usage),
+            });
+        };
+        self.offset += end as u64 + 1;
+
+        let new_lines: Vec<String> = String::from_utf8_lossy(&appended[..end])
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(String::from)
+            .collect();
+
+        let mut lines = std::mem::take(&mut self.pending);
+        let pending_count = lines.len();
+        lines.extend(new_lines);
+
+        let mut records: Vec<(String, Message)> = vec![];
+        for (i, line) in lines.into_iter().enumerate() {
+            let Ok(message) = serde_json::from_str::<Message>(&line) else {
+                continue;
+            };
+            if message.is_sidechain {
+                continue;
+            }
+            if i >= pending_count {
+                if let Some(usage) = message.usage() {
+                    self.usage.add(usage);
+                }
+            }
+            records.push((line, message));
+        }
+
+        let (lines, mut messages): (Vec<String>, Vec<Message>) = records.into_iter().unzip();
+        attach_tool_calls(&mut messages);
+
+        self.pending = lines
+            .into_iter()
+            .zip(&messages)
+            .filter(|(_, msg)| msg.tool_calls.iter().any(|call| call.output.is_none()))
+            .map(|(line, _)| line)
+            .collect();
+        let overflow = self.pending.len().saturating_sub(MAX_PENDING);
+        self.pending.drain(..overflow);
+
+        Ok(TailUpdate {
+            messages: messages.into_iter().filter(is_visible).collect(),
+            usage: (self.usage != previous_usage).then_some(self.usage),
+        })
+    }
+}
+
+/// What has been sent about an OpenCode session, whose messages are files of
+/// their own rather than lines of one file.
+#[derive(Default)]
+struct OpenCodeSeen {
+    /// IDs of the messages sent, or counted as sent; `None` until the
+    /// session is first read.
+    message_ids: Option<HashSet<String>>,
+    /// The last message sent, which may still have been streaming.
+    last_id: Option<String>,
+    usage: Option<UsageTotals>,
+}
+
+/// Sessions being followed, and where. [`watch_sessions`] hands it every
+/// batch of changed paths.
+pub struct WatchState {
+    claude_root: Option<PathBuf>,
+    opencode: Option<OpenCodeStorageSource>,
+    claude: HashMap<PathBuf, SessionTail>,
+    opencode_seen: HashMap<String, OpenCodeSeen>,
+    /// Session of every OpenCode message seen, as parts only name their
+    /// message in their path.
+    message_sessions: HashMap<String, String>,
+}
+
+impl WatchState {
+    /// Follows the Claude Code sessions under `claude_root` from their
+    /// current end, and the sessions in OpenCode's storage.
+    pub fn new(claude_root: Option<PathBuf>, opencode: Option<OpenCodeStorageSource>) -> Self {
+        let mut claude = HashMap::new();
+        if let Some(root) = &claude_root {
+            for project in fs::read_dir(root).into_iter().flatten().flatten() {
+                for session in list_jsonl_files(&project.path()).unwrap_or_default() {
+                    let tail = SessionTail::at_end(&session);
+                    claude.insert(session, tail);
+                }
+            }
+        }
+
+        Self {
+            claude_root,
+            opencode,
+            claude,
+            opencode_seen: HashMap::new(),
+            message_sessions: HashMap::new(),
+        }
+    }
+
+    /// Reads a batch of changed paths. An OpenCode session is read once
+    /// however many of its files changed.
+    pub fn changed(&mut self, paths: &BTreeSet<PathBuf>, on_event: &mut impl FnMut(WatchEvent)) {
+        // Changed OpenCode sessions, and whether their info file changed.
+        let mut opencode_sessions: BTreeMap<String, bool> = BTreeMap::new();
+        let opencode_root = self.opencode.as_ref().map(|s| s.root().to_path_buf());
+
+        for path in paths {
+            if let Some(root) = &self.claude_root {
+                if let Ok(relative) = path.strip_prefix(root) {
+                    // Only `<project>/<session>.jsonl`; subagent transcripts
+                    // are further down.
+                    if relative.components().count() == 2
+                        && path.extension().is_some_and(|e| e == "jsonl")
+                    {
+                        self.claude_changed(path, on_event);
+                    }
+                    continue;
+                }
+            }
+
+            if let Some(root) = &opencode_root {
+                if let Ok(relative) = path.strip_prefix(root) {
+                    if let Some(session_id) = self.opencode_session_id(root, relative) {
+                        *opencode_sessions.entry(session_id).or_default() |=
+                            relative.starts_with("session");
+                    }
+                }
+            }
+        }
+
+        for (session_id, info_changed) in opencode_sessions {
+            self.opencode_changed(&session_id, info_changed, on_event);
+        }
+    }
+
+    /// The session a file under OpenCode's storage belongs to: the info file
+    /// `session/<project>/<session>.json`, a message
+    /// `message/<session>/<message>.json`, or a part
+    /// `part/<message>/<part>.json`, by the session of its message.
+    fn opencode_session_id(&mut self, root: &Path, relative: &Path) -> Option<String> {
+        if relative.extension().is_none_or(|e| e != "json") {
+            return None;
+        }
+
+        let mut components = relative.components().map(|c| c.as_os_str().to_str());
+        let kind = components.next()??;
+        let dir = components.next()??;
+        let stem = relative.file_stem()?.to_str()?;
+
+        match kind {
+            "session" => Some(stem.to_string()),
+            "message" => {
+                self.message_sessions
+                    .insert(stem.to_string(), dir.to_string());
+                Some(dir.to_string())
+            }
+            "part" => {
+                if let Some(session_id) = self.message_sessions.get(dir) {
+                    return Some(session_id.clone());
+                }
+
+                // A message written before watching started.
+                let message_file = format!("{}.json", dir);
+                let session_id = fs::read_dir(root.join("message"))
+                    .ok()?
+                    .flatten()
+                    .find(|entry| entry.path().join(&message_file).is_file())?
+                    .file_name()
+                    .into_string()
+                    .ok()?;
+                self.message_sessions
+                    .insert(dir.to_string(), session_id.clone());
+                Some(session_id)
+            }
+            _ => None,
+        }
+    }
+
+    fn claude_changed(&mut self, path: &Path, on_event: &mut impl FnMut(WatchEvent)) {
+        if !path.is_file() {
+            return;
+        }
+
+        let session_path = path.to_string_lossy().to_string();
+        let tail = self.claude.entry(path.to_path_buf()).or_insert_with(|| {
+            on_event(WatchEvent::SessionCreated {
+                project_path: path
+                    .parent()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                session_path: session_path.clone(),
+            });
+            SessionTail::new()
+        });
+
+        let Ok(update) = tail.read(path) else {
+            return;
+        };
+
+        if !update.messages.is_empty() {
+            on_event(WatchEvent::MessagesAppended {
+                session_path: session_path.clone(),
+                messages: update.messages,
+            });
+        }
+        if let Some(usage) = update.usage {
+            on_event(WatchEvent::TokensChanged {
+                session_path,
+                usage,
+            });
+        }
+    }
+
+    fn opencode_changed(
+        &mut self,
+        session_id: &str,
+        info_changed: bool,
+        on_event: &mut impl FnMut(WatchEvent),
+    ) {
+        let Some(opencode) = &self.opencode else {
+            return;
+        };
+        if !opencode.lists_session(session_id) {
+            return;
+        }
+        let Some(info_path) = opencode.session_info_path(session_id) else {
+            return;
+        };
+        let session_path = info_path.to_string_lossy().to_string();
+
+        let is_new = !self.opencode_seen.contains_key(session_id);
+        let seen = self
+            .opencode_seen
+            .entry(session_id.to_string())
+            .or_default();
+
+        if is_new && info_changed {
+            on_event(WatchEvent::SessionCreated {
+                project_path: info_path
+                    .parent()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                session_path: session_path.clone(),
+            });
+            seen.message_ids = Some(HashSet::new());
+        }
+
+        // Storage only: a session whose messages are not written yet must
+        // not fall back to running `opencode export`.
+        let Ok(messages) = opencode.storage_messages(session_id) else {
+            return;
+        };
+        for message in &messages {
+            if let Some(uuid) = &message.uuid {
+                self.message_sessions
+                    .insert(uuid.clone(), session_id.to_string());
+            }
+        }
+
+        let mut usage = UsageTotals::default();
+        for message in &messages {
+            if let Some(message_usage) = message.usage() {
+                usage.add(message_usage);
+            }
+        }
+
+        // Messages are told apart by ID, as ones created within the same
+        // millisecond may be listed in either order. For a session first
+        // seen now, only the latest message is sent.
+        let sent = seen.message_ids.get_or_insert_with(|| {
+            messages
+                .iter()
+                .rev()
+                .skip(1)
+                .filter_map(|m| m.uuid.clone())
+                .collect()
+        });
+        // The last message sent may still have been streaming, so it is sent
+        // again.
+        let resent = seen.last_id.take();
+        seen.last_id = messages.last().and_then(|m| m.uuid.clone());
+        let appended: Vec<Message> = messages
+            .into_iter()
+            .filter(|m| match &m.uuid {
+                Some(id) => resent.as_ref() == Some(id) || sent.insert(id.clone()),
+                None => false,
+            })
+            .filter(is_visible)
+            .collect();
+
+        if !appended.is_empty() {
+            on_event(WatchEvent::MessagesAppended {
+                session_path: session_path.clone(),
+                messages: appended,
+            });
+        }
+        if seen.usage != Some(usage) {
+            seen.usage = Some(usage);
+            on_event(WatchEvent::TokensChanged {
+                session_path,
+                usage,
+            });
+        }
+    }
+}
+
+/// Watches the Claude Code and OpenCode session directories and calls
+/// `on_event` with what changes in them, for as long as the process runs.
+/// Returns only if the directories cannot be watched.
+pub fn watch_sessions(
+    registry: &SourceRegistry,
+    mut on_event: impl FnMut(WatchEvent),
+) -> Result<(), String> {
+    let claude_root = registry
+        .get("claude-projects")
+        .map(|source| source.root().to_path_buf())
+        .filter(|root| root.is_dir());
+    let opencode = registry
+        .opencode()
+        .filter(|source| source.root().is_dir())
+        .cloned();
+
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher: RecommendedWatcher =
+        notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+
+    let roots = claude_root
+        .as_deref()
+        .into_iter()
+        .chain(opencode.as_ref().map(|source| source.root()));
+    for root in roots {
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(|e| format!("Cannot watch {}: {}", root.display(), e))?;
+    }
+
+    let mut state = WatchState::new(claude_root, opencode);
+
+    while let Ok(first) = rx.recv() {
+        let mut changed = BTreeSet::new();
+        collect_paths(first, &mut changed);
+
+        let deadline = Instant::now() + DEBOUNCE;
+        while let Ok(next) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            collect_paths(next, &mut changed);
+        }
+
+        state.changed(&changed, &mut on_event);
+    }
+
+    Ok(())
+}
+
+fn collect_paths(event: notify::Result<Event>, paths: &mut BTreeSet<PathBuf>) {
+    let Ok(event) = event else {
+        return;
+    };
+    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        paths.extend(event.paths);
+    }
+}
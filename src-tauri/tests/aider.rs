@@ -0,0 +1,118 @@
+use agent_log_lib::extract_text_content;
+use agent_log_lib::sources::{project_display_name, AiderSource, SessionSource};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use std::path::{Path, PathBuf};
+
+fn repo() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/aider/repo")
+}
+
+fn session(id: &str) -> PathBuf {
+    repo().join(".aider.chat.history.md").join(id)
+}
+
+fn local_time(timestamp: Option<&str>) -> NaiveDateTime {
+    DateTime::parse_from_rfc3339(timestamp.unwrap())
+        .unwrap()
+        .naive_local()
+}
+
+#[test]
+fn gives_every_repository_its_own_id() {
+    let source = AiderSource::new(repo());
+    let other = AiderSource::new(repo().join("nested"));
+
+    assert_eq!(source.id(), format!("aider:{}", repo().display()));
+    assert_ne!(source.id(), other.id());
+}
+
+#[test]
+fn lists_one_session_per_chat() {
+    let source = AiderSource::new(repo());
+
+    let projects = source.list_projects().unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(
+        projects[0].name,
+        format!("{} (Aider)", project_display_name(&repo()))
+    );
+    assert_eq!(projects[0].session_count, 2);
+
+    let sessions = source.list_session_paths(&repo()).unwrap();
+    assert_eq!(
+        sessions,
+        [
+            session("2025-01-05T10-00-00"),
+            session("2025-01-05T10-00-00-2")
+        ]
+    );
+}
+
+#[test]
+fn reads_prompts_and_replies() {
+    let messages = AiderSource::new(repo())
+        .load_messages(&session("2025-01-05T10-00-00"))
+        .unwrap();
+
+    let roles: Vec<_> = messages.iter().map(|m| m.role().unwrap()).collect();
+    assert_eq!(roles, ["user", "assistant", "user", "assistant"]);
+    assert_eq!(
+        extract_text_content(&messages[0].message),
+        "Add a hello function"
+    );
+    assert!(extract_text_content(&messages[1].message)
+        .starts_with("Sure. Here is the change:\n\nhello.py\n```python"));
+    assert_eq!(
+        extract_text_content(&messages[2].message),
+        "Now call it\nfrom main"
+    );
+    assert_eq!(messages[1].model(), Some("claude-3-5-sonnet-20241022"));
+    assert_eq!(messages[0].model(), None);
+}
+
+#[test]
+fn times_prompts_from_the_input_history() {
+    let messages = AiderSource::new(repo())
+        .load_messages(&session("2025-01-05T10-00-00"))
+        .unwrap();
+
+    let day = NaiveDate::from_ymd_opt(2025, 1, 5).unwrap();
+    assert_eq!(
+        local_time(messages[0].timestamp.as_deref()),
+        day.and_hms_micro_opt(10, 0, 5, 123_456).unwrap()
+    );
+    // Replies take the time of the prompt they answer.
+    assert_eq!(
+        local_time(messages[3].timestamp.as_deref()),
+        day.and_hms_micro_opt(10, 2, 0, 1).unwrap()
+    );
+
+    let second = AiderSource::new(repo())
+        .load_messages(&session("2025-01-05T10-00-00-2"))
+        .unwrap();
+    assert_eq!(
+        local_time(second[0].timestamp.as_deref()),
+        day.and_hms_milli_opt(10, 5, 0, 500).unwrap()
+    );
+    assert_eq!(second[1].model(), Some("gpt-4o"));
+}
+
+#[test]
+fn reads_token_reports() {
+    let messages = AiderSource::new(repo())
+        .load_messages(&session("2025-01-05T10-00-00"))
+        .unwrap();
+
+    let first = messages[1].usage().unwrap();
+    assert_eq!(first.input_tokens, Some(2_100));
+    assert_eq!(first.cache_read_input_tokens, Some(1_500));
+    assert_eq!(first.output_tokens, Some(150));
+    assert_eq!(first.cost, Some(0.01));
+
+    let second = messages[3].usage().unwrap();
+    assert_eq!(second.input_tokens, Some(1_200_000));
+    assert_eq!(second.output_tokens, Some(40));
+    assert_eq!(second.cost, None);
+
+    assert!(messages[0].usage().is_none());
+}
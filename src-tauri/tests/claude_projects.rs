@@ -0,0 +1,45 @@
+use agent_log_lib::sources::{decode_dashed_dir, ClaudeProjectsSource, SessionSource};
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/claude")
+}
+
+#[test]
+fn names_projects_after_the_recorded_cwd() {
+    let mut projects = ClaudeProjectsSource::new(fixtures())
+        .list_projects()
+        .unwrap();
+    projects.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let names: Vec<_> = projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["home/me/my-app", "srv/no/cwd"]);
+}
+
+#[test]
+fn finds_the_working_dir_of_a_session() {
+    let source = ClaudeProjectsSource::new(fixtures());
+    let with_cwd = fixtures().join("-home-me-my-app/5d1c2f7a-0b9e-4c3a-8f21-6e4b7a9d0c11.jsonl");
+    let without_cwd = fixtures().join("-srv-no-cwd/0e7f3b2c-9a41-4d6e-b5c8-1f2a3b4c5d6e.jsonl");
+
+    assert_eq!(
+        source.working_dir(&with_cwd),
+        Some(PathBuf::from("/home/me/my-app"))
+    );
+    assert_eq!(
+        source.working_dir(&without_cwd),
+        Some(PathBuf::from("/srv/no/cwd"))
+    );
+}
+
+#[test]
+fn decodes_dashed_names_against_existing_directories() {
+    let temp = tempfile::tempdir().unwrap();
+    let base = temp.path().canonicalize().unwrap();
+    let dir = base.join("my-app").join(".config");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let dashed = dir.to_string_lossy().replace(['/', '.'], "-");
+
+    assert_eq!(decode_dashed_dir(&dashed), dir);
+}
@@ -0,0 +1,67 @@
+use agent_log_lib::extract_text_content;
+use agent_log_lib::sources::{ClineSource, SessionSource};
+use std::path::{Path, PathBuf};
+
+fn tasks_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cline/tasks")
+}
+
+fn task() -> PathBuf {
+    tasks_dir().join("1736067600000")
+}
+
+#[test]
+fn lists_tasks_with_a_conversation() {
+    let source = ClineSource::cline(tasks_dir());
+    let projects = source.list_projects().unwrap();
+
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "Cline Tasks");
+    assert_eq!(projects[0].session_count, 1);
+
+    let sessions = source.list_sessions(&tasks_dir()).unwrap();
+    assert_eq!(sessions[0].id, "1736067600000");
+    assert_eq!(
+        sessions[0].title.as_deref(),
+        Some("Rename the greet function to hello")
+    );
+    assert_eq!(sessions[0].input_tokens, 1500);
+    assert_eq!(sessions[0].output_tokens, 100);
+    assert_eq!(sessions[0].cache_read_tokens, 900);
+    assert_eq!(sessions[0].cache_write_tokens, 900);
+    assert!((sessions[0].cost.unwrap() - 0.0144).abs() < 1e-9);
+}
+
+#[test]
+fn reads_messages_without_environment_details() {
+    let source = ClineSource::roo_code(tasks_dir());
+    let messages = source.load_messages(&task()).unwrap();
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        extract_text_content(&messages[0].message),
+        "Rename the greet function to hello"
+    );
+    assert_eq!(
+        messages[0].timestamp.as_deref(),
+        Some("2025-01-05T09:00:01+00:00")
+    );
+
+    let calls = &messages[1].tool_calls;
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].name, "read_file");
+    assert_eq!(
+        calls[0].output.as_deref(),
+        Some("export function greet() {}")
+    );
+    assert_eq!(calls[0].duration_ms, Some(9000));
+
+    let usage = messages[1].usage().unwrap();
+    assert_eq!(usage.input_tokens, Some(1200));
+    assert_eq!(usage.cost, Some(0.0123));
+
+    assert_eq!(
+        extract_text_content(&messages[2].message),
+        "Renamed greet to hello."
+    );
+}
@@ -0,0 +1,143 @@
+use agent_log_lib::sources::{CodexSource, SessionSource};
+use agent_log_lib::{extract_block_text, extract_text_content, BlockKind, Message};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/codex")
+}
+
+fn rollout() -> PathBuf {
+    fixtures()
+        .join("2025/10/01/rollout-2025-10-01T09-00-00-0199a1b2-7c3d-7e4f-9a0b-1c2d3e4f5a6b.jsonl")
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir(&path, &to.join(entry.file_name()));
+        } else {
+            fs::copy(&path, to.join(entry.file_name())).unwrap();
+        }
+    }
+}
+
+fn project_counts(source: &CodexSource) -> Vec<(String, usize)> {
+    source
+        .list_projects()
+        .unwrap()
+        .into_iter()
+        .map(|p| (p.name, p.session_count))
+        .collect()
+}
+
+#[test]
+fn groups_rollouts_by_working_directory() {
+    let source = CodexSource::new(fixtures());
+
+    // The older rollout only names its directory in the environment context.
+    assert_eq!(
+        project_counts(&source),
+        [
+            ("home/me/api (Codex)".to_string(), 1),
+            ("home/me/app (Codex)".to_string(), 2),
+        ]
+    );
+
+    let app = fixtures().join("home/me/app");
+    let mut sessions = source.list_session_paths(&app).unwrap();
+    sessions.sort();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0], rollout());
+    assert_eq!(
+        source.working_dir(&sessions[1]),
+        Some(PathBuf::from("/home/me/app"))
+    );
+}
+
+#[test]
+fn reads_messages_without_injected_prompts() {
+    let source = CodexSource::new(fixtures());
+    let messages = source.load_messages(&rollout()).unwrap();
+
+    assert_eq!(
+        extract_text_content(&messages[0].message),
+        "Add a health check endpoint"
+    );
+    let call = messages
+        .iter()
+        .flat_map(|m| &m.tool_calls)
+        .find(|c| c.id == "call_1")
+        .unwrap();
+    assert_eq!(call.name, "shell");
+    assert_eq!(call.output.as_deref(), Some("test result: ok"));
+    assert!(!call.is_error);
+
+    let records: Vec<Message> = source.records(&rollout()).unwrap().collect();
+    assert_eq!(
+        extract_block_text(&records[1], BlockKind::Thinking),
+        "Looking at the routes first"
+    );
+}
+
+#[test]
+fn takes_usage_from_growth_of_the_token_totals() {
+    let source = CodexSource::new(fixtures());
+    let messages: Vec<Message> = source.records(&rollout()).unwrap().collect();
+    let usage: Vec<_> = messages.iter().filter_map(|m| m.usage()).collect();
+
+    // The repeated totals add nothing; cached input is left out of the input.
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0].input_tokens, Some(800));
+    assert_eq!(usage[0].cache_read_input_tokens, Some(200));
+    assert_eq!(usage[0].output_tokens, Some(50));
+    assert_eq!(usage[0].reasoning_tokens, Some(10));
+    assert_eq!(usage[1].input_tokens, Some(500));
+    assert_eq!(usage[1].cache_read_input_tokens, Some(1000));
+    assert_eq!(usage[1].output_tokens, Some(80));
+    assert_eq!(usage[1].reasoning_tokens, Some(20));
+
+    let last = messages.last().unwrap();
+    assert!(last.usage().is_some());
+    assert_eq!(last.model(), Some("gpt-5-codex"));
+}
+
+#[test]
+fn regroups_when_rollouts_are_added_or_resolved() {
+    let temp = tempfile::tempdir().unwrap();
+    copy_dir(&fixtures(), temp.path());
+    let source = CodexSource::new(temp.path().to_path_buf());
+    assert_eq!(project_counts(&source).len(), 2);
+
+    // A rollout in a new directory, before Codex has written its metadata.
+    let day = temp.path().join("2025/10/03");
+    fs::create_dir_all(&day).unwrap();
+    let added = day.join("rollout-2025-10-03T08-00-00-0199a3d4-0000-7000-8000-000000000001.jsonl");
+    fs::write(&added, "").unwrap();
+    assert_eq!(
+        project_counts(&source),
+        [
+            ("Codex".to_string(), 1),
+            ("home/me/api (Codex)".to_string(), 1),
+            ("home/me/app (Codex)".to_string(), 2),
+        ]
+    );
+
+    // Once it records a working directory it moves to that project.
+    let mut file = fs::OpenOptions::new().append(true).open(&added).unwrap();
+    writeln!(
+        file,
+        r#"{{"timestamp":"2025-10-03T08:00:00.000Z","type":"session_meta","payload":{{"id":"0199a3d4-0000-7000-8000-000000000001","cwd":"/home/me/api"}}}}"#
+    )
+    .unwrap();
+    assert_eq!(
+        project_counts(&source),
+        [
+            ("home/me/api (Codex)".to_string(), 2),
+            ("home/me/app (Codex)".to_string(), 2),
+        ]
+    );
+}
@@ -0,0 +1,122 @@
+use agent_log_lib::extract_text_content;
+use agent_log_lib::sources::{CustomSource, Mapping, SessionSource};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+fn logs() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/custom/logs")
+}
+
+fn mapping(name: &str) -> serde_json::Value {
+    json!({
+        "name": name,
+        "root": logs(),
+        "fields": {
+            "role": "/who",
+            "text": "/body",
+            "timestamp": "/ts",
+            "session_id": "/sid",
+            "model": "/model",
+            "input_tokens": "/usage/in",
+            "output_tokens": "/usage/out",
+            "cost": "/cost"
+        },
+        "roles": { "human": "user", "ai": "assistant" }
+    })
+}
+
+fn load(mapping: &serde_json::Value) -> Result<Mapping, String> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mapping.json");
+    std::fs::write(&path, mapping.to_string()).unwrap();
+    Mapping::load(&path)
+}
+
+fn source() -> CustomSource {
+    CustomSource::new(load(&mapping("Team chat")).unwrap())
+}
+
+#[test]
+fn validates_mappings() {
+    let mut relative = mapping("Team chat");
+    relative["fields"]["model"] = json!("model");
+    let err = load(&relative).unwrap_err();
+    assert!(err.contains("\"model\" is not a JSON pointer"), "{}", err);
+
+    let mut unknown = mapping("Team chat");
+    unknown["fields"]["tokens"] = json!("/tokens");
+    assert!(load(&unknown).unwrap_err().contains("unknown field"));
+
+    let mut missing = mapping("Team chat");
+    missing["fields"].as_object_mut().unwrap().remove("text");
+    assert!(load(&missing).unwrap_err().contains("missing field"));
+}
+
+#[test]
+fn gives_every_mapping_its_own_id() {
+    let team = source();
+    let other = CustomSource::new(load(&mapping("Other")).unwrap());
+
+    assert_eq!(team.id(), "custom:Team chat");
+    assert_ne!(team.id(), other.id());
+}
+
+#[test]
+fn addresses_session_ids_with_slashes_and_percents() {
+    let source = source();
+    let file = logs().join("chat.jsonl");
+
+    let sessions = source.list_session_paths(&logs()).unwrap();
+    assert_eq!(sessions, [file.join("team%2Falpha"), file.join("50%25")]);
+
+    let messages = source.load_messages(&sessions[0]).unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].session_id.as_deref(), Some("team/alpha"));
+    assert_eq!(extract_text_content(&messages[0].message), "Deploy the app");
+    assert_eq!(
+        messages[0].timestamp.as_deref(),
+        Some("2025-01-01T10:00:00+00:00")
+    );
+    assert_eq!(
+        messages[1].timestamp.as_deref(),
+        Some("2025-01-01T10:00:05+00:00")
+    );
+    assert_eq!(messages[1].model(), Some("llama-3"));
+
+    let usage = messages[1].usage().unwrap();
+    assert_eq!(usage.input_tokens, Some(120));
+    assert_eq!(usage.output_tokens, Some(30));
+    assert_eq!(usage.cost, Some(0.002));
+}
+
+#[test]
+fn reports_lines_that_cannot_be_read() {
+    let source = source();
+    let session = logs().join("chat.jsonl").join("50%25");
+
+    let errors: Vec<_> = source
+        .line_errors(&session)
+        .unwrap()
+        .into_iter()
+        .map(|e| (e.line, e.message))
+        .collect();
+
+    assert_eq!(errors.len(), 4, "{:?}", errors);
+    assert_eq!(errors[0].0, 4);
+    assert!(errors[0].1.starts_with("not JSON"));
+    assert_eq!(
+        errors[1],
+        (5, "timestamp \"yesterday\" is not RFC 3339".to_string())
+    );
+    assert_eq!(errors[2], (7, "no text at /body".to_string()));
+    assert_eq!(errors[3], (8, "role at /who is not a string".to_string()));
+
+    // A timestamp out of range is left out rather than failing the line.
+    let messages = source.load_messages(&session).unwrap();
+    let texts: Vec<_> = messages
+        .iter()
+        .map(|m| extract_text_content(&m.message))
+        .collect();
+    assert_eq!(texts, ["hello", "from before time"]);
+    assert_eq!(messages[1].timestamp, None);
+}
@@ -0,0 +1,123 @@
+use agent_log_lib::sources::{GeminiSource, SessionSource};
+use agent_log_lib::{extract_block_text, extract_text_content, BlockKind};
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/gemini")
+}
+
+fn project_dir() -> PathBuf {
+    fixtures().join("3f2a9c1e5b7d4a60")
+}
+
+fn source() -> GeminiSource {
+    GeminiSource::new(fixtures())
+}
+
+#[test]
+fn lists_projects_with_sessions() {
+    let projects = source().list_projects().unwrap();
+
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "home/me/gemini-app (Gemini)");
+    assert_eq!(projects[0].session_count, 2);
+}
+
+#[test]
+fn names_projects_without_a_root_file_by_hash() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["c0ffee0123456789", "ünïcdéf-hash"] {
+        let project = dir.path().join(name);
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::copy(
+            project_dir().join("checkpoint-refactor.json"),
+            project.join("checkpoint-refactor.json"),
+        )
+        .unwrap();
+    }
+
+    let mut projects = GeminiSource::new(dir.path().to_path_buf())
+        .list_projects()
+        .unwrap();
+    projects.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let names: Vec<_> = projects.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["Gemini c0ffee01", "Gemini ünïcdéf-"]);
+}
+
+#[test]
+fn reads_chat_messages_tool_calls_and_tokens() {
+    let source = source();
+    let session = project_dir().join("chats/session-2025-09-10T09-00-1a2b3c4d.json");
+    let messages = source.load_messages(&session).unwrap();
+
+    let roles: Vec<_> = messages.iter().map(|m| m.role().unwrap()).collect();
+    assert_eq!(roles, ["user", "assistant", "assistant"]);
+    assert_eq!(
+        extract_text_content(&messages[0].message),
+        "What is in package.json?"
+    );
+
+    let calls = &messages[1].tool_calls;
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].name, "read_file");
+    assert_eq!(
+        calls[0].input["absolute_path"],
+        "/home/me/gemini-app/package.json"
+    );
+    assert_eq!(
+        calls[0].output.as_deref(),
+        Some("{ \"name\": \"gemini-app\" }")
+    );
+    assert!(!calls[0].is_error);
+    assert!(calls[1].is_error);
+    assert_eq!(
+        calls[1].output.as_deref(),
+        Some("Command exited with code 1")
+    );
+
+    let tool_use = extract_block_text(&messages[1], BlockKind::ToolUse);
+    assert!(tool_use.contains("read_file\n"));
+    assert!(tool_use.contains("/home/me/gemini-app/package.json"));
+    let tool_result = extract_block_text(&messages[1], BlockKind::ToolResult);
+    assert!(tool_result.contains("Command exited with code 1"));
+
+    let usage = messages[1].usage().unwrap();
+    assert_eq!(usage.input_tokens, Some(1000));
+    assert_eq!(usage.cache_read_input_tokens, Some(200));
+    assert_eq!(usage.output_tokens, Some(40));
+    assert_eq!(usage.reasoning_tokens, Some(15));
+    assert_eq!(messages[1].model(), Some("gemini-2.5-pro"));
+
+    let sessions = source.list_sessions(&project_dir()).unwrap();
+    let chat = sessions
+        .iter()
+        .find(|s| s.id == "session-2025-09-10T09-00-1a2b3c4d")
+        .unwrap();
+    assert_eq!(chat.input_tokens, 1300);
+    assert_eq!(chat.output_tokens, 50);
+    assert_eq!(chat.cache_read_tokens, 1200);
+}
+
+#[test]
+fn reads_checkpoints_without_the_setup_exchange() {
+    let source = source();
+    let checkpoint = project_dir().join("checkpoint-refactor.json");
+    let messages = source.load_messages(&checkpoint).unwrap();
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        extract_text_content(&messages[0].message),
+        "List the source files"
+    );
+
+    let calls = &messages[1].tool_calls;
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].name, "list_directory");
+    assert_eq!(calls[0].output.as_deref(), Some("main.rs\nlib.rs"));
+
+    assert_eq!(
+        source.session_title(&checkpoint).as_deref(),
+        Some("refactor")
+    );
+}
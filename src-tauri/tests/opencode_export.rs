@@ -0,0 +1,101 @@
+use agent_log_lib::config::OpenCodeConfig;
+use agent_log_lib::extract_text_content;
+use agent_log_lib::sources::{
+    import_export, OpenCodeError, OpenCodeExportsSource, OpenCodeStorageSource, SessionSource,
+};
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/opencode-export")
+}
+
+fn source() -> OpenCodeExportsSource {
+    let storage =
+        OpenCodeStorageSource::new(fixtures().join("no-storage"), OpenCodeConfig::default());
+    OpenCodeExportsSource::new(fixtures(), storage)
+}
+
+#[test]
+fn reads_exports_after_log_lines() {
+    let source = source();
+    let session = fixtures().join("ses_7d2c9a.json");
+
+    assert_eq!(
+        source.session_title(&session).as_deref(),
+        Some("Fix the login form")
+    );
+
+    let messages = source.load_messages(&session).unwrap();
+    let ids: Vec<_> = messages
+        .iter()
+        .map(|m| m.uuid.as_deref().unwrap())
+        .collect();
+    assert_eq!(ids, ["msg_01", "msg_02", "msg_04"]);
+    assert_eq!(
+        extract_text_content(&messages[0].message),
+        "The login form ignores Enter"
+    );
+    assert_eq!(messages[0].session_id.as_deref(), Some("ses_7d2c9a"));
+
+    let call = &messages[1].tool_calls[0];
+    assert_eq!(call.name, "read");
+    assert_eq!(call.input["filePath"], "src/login.tsx");
+    assert_eq!(call.duration_ms, Some(250));
+}
+
+#[test]
+fn keeps_parent_links_and_models() {
+    let messages: Vec<_> = source()
+        .records(&fixtures().join("ses_7d2c9a.json"))
+        .unwrap()
+        .collect();
+
+    let parents: Vec<_> = messages.iter().map(|m| m.parent_uuid.as_deref()).collect();
+    // Steps of one reply are chained rather than left as siblings.
+    assert_eq!(
+        parents,
+        [None, Some("msg_01"), Some("msg_02"), Some("msg_03")]
+    );
+    assert_eq!(messages[1].model(), Some("claude-sonnet-4"));
+    assert_eq!(messages[0].model(), None);
+}
+
+#[test]
+fn keeps_the_usage_of_steps_without_text() {
+    let messages: Vec<_> = source()
+        .records(&fixtures().join("ses_7d2c9a.json"))
+        .unwrap()
+        .collect();
+
+    let usage = messages[2].usage().unwrap();
+    assert_eq!(usage.input_tokens, Some(1500));
+    assert_eq!(usage.output_tokens, Some(20));
+    assert_eq!(usage.cache_read_input_tokens, Some(1200));
+
+    let sessions = source().list_sessions(&fixtures()).unwrap();
+    let session = sessions.iter().find(|s| s.id == "ses_7d2c9a").unwrap();
+    assert_eq!(session.input_tokens, 2700);
+    assert_eq!(session.output_tokens, 100);
+}
+
+#[test]
+fn imports_under_the_session_id() {
+    let export_dir = tempfile::tempdir().unwrap();
+
+    let saved = import_export(&fixtures().join("ses_7d2c9a.json"), export_dir.path()).unwrap();
+
+    assert_eq!(saved, export_dir.path().join("ses_7d2c9a.json"));
+    assert!(saved.is_file());
+}
+
+#[test]
+fn rejects_session_ids_that_leave_the_export_dir() {
+    let base = tempfile::tempdir().unwrap();
+    let export_dir = base.path().join("a/b");
+
+    let result = import_export(&fixtures().join("traversal.json"), &export_dir);
+
+    assert!(matches!(result, Err(OpenCodeError::InvalidExport { .. })));
+    assert!(!base.path().join("escaped.json").exists());
+    assert!(!export_dir.exists());
+}
@@ -0,0 +1,145 @@
+use agent_log_lib::index::SessionIndex;
+use agent_log_lib::repos::{repo_groups, RepoGroup};
+use agent_log_lib::sources::{ClaudeProjectsSource, SourceRegistry};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const REMOTE: &str = "git@example.com:me/app.git";
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+fn init_repo(dir: &Path, remote: Option<&str>) {
+    fs::create_dir_all(dir).unwrap();
+    git(dir, &["init", "-q"]);
+    git(dir, &["commit", "-q", "--allow-empty", "-m", "init"]);
+    if let Some(remote) = remote {
+        git(dir, &["remote", "add", "origin", remote]);
+    }
+}
+
+/// A Claude Code session started in `cwd`.
+fn add_session(claude_root: &Path, project: &str, cwd: &Path) {
+    let dir = claude_root.join(project);
+    fs::create_dir_all(&dir).unwrap();
+    let line = serde_json::json!({
+        "type": "user",
+        "uuid": format!("{}-1", project),
+        "cwd": cwd,
+        "timestamp": "2025-10-01T09:00:00.000Z",
+        "message": { "role": "user", "content": "hello" }
+    });
+    fs::write(dir.join(format!("{}.jsonl", project)), line.to_string()).unwrap();
+}
+
+fn group_with_path<'a>(groups: &'a [RepoGroup], path: &Path) -> &'a RepoGroup {
+    let path = Some(path.to_string_lossy().to_string());
+    groups
+        .iter()
+        .find(|g| g.workdirs.iter().any(|w| w.path == path))
+        .unwrap()
+}
+
+fn workdir_paths(group: &RepoGroup) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = group
+        .workdirs
+        .iter()
+        .map(|w| PathBuf::from(w.path.as_ref().unwrap()))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn groups_sessions_by_repository() {
+    let temp = tempfile::tempdir().unwrap();
+    let base = temp.path().canonicalize().unwrap();
+
+    let app = base.join("app");
+    init_repo(&app, Some(REMOTE));
+    git(
+        &app,
+        &["worktree", "add", "-q", "-b", "feature", "../app-wt"],
+    );
+    fs::create_dir_all(app.join("sub")).unwrap();
+    let clone = base.join("clone");
+    init_repo(&clone, Some(REMOTE));
+    let local = base.join("local");
+    init_repo(&local, None);
+    fs::create_dir_all(local.join("a")).unwrap();
+    fs::create_dir_all(local.join("b")).unwrap();
+    git(
+        &local,
+        &["worktree", "add", "-q", "-b", "fix", "../local-wt"],
+    );
+    let plain = base.join("plain");
+    fs::create_dir_all(&plain).unwrap();
+
+    let claude_root = base.join("claude");
+    add_session(&claude_root, "app", &app);
+    add_session(&claude_root, "wt", &base.join("app-wt"));
+    add_session(&claude_root, "sub", &app.join("sub"));
+    add_session(&claude_root, "clone", &clone);
+    add_session(&claude_root, "local-a", &local.join("a"));
+    add_session(&claude_root, "local-b", &local.join("b"));
+    add_session(&claude_root, "local-wt", &base.join("local-wt"));
+    add_session(&claude_root, "plain", &plain);
+
+    let mut registry = SourceRegistry::default();
+    registry.register(ClaudeProjectsSource::new(claude_root));
+    let index = SessionIndex::open_in_memory().unwrap();
+
+    let groups = repo_groups(&registry, &index).unwrap();
+    assert_eq!(groups.len(), 3);
+
+    // Clones with the same remote are one repository, across worktrees and
+    // subdirectories.
+    let remote = group_with_path(&groups, &app);
+    assert_eq!(remote.remote.as_deref(), Some(REMOTE));
+    assert_eq!(remote.totals.session_count, 4);
+    assert_eq!(
+        workdir_paths(remote),
+        [
+            app.clone(),
+            app.join("sub"),
+            base.join("app-wt"),
+            clone.clone()
+        ]
+    );
+    let worktree = |path: &Path| {
+        let path = Some(path.to_string_lossy().to_string());
+        let workdir = remote.workdirs.iter().find(|w| w.path == path).unwrap();
+        PathBuf::from(workdir.worktree.as_ref().unwrap())
+    };
+    assert_eq!(worktree(&app.join("sub")), app);
+    assert_eq!(worktree(&base.join("app-wt")), base.join("app-wt"));
+    assert_eq!(worktree(&clone), clone);
+
+    // Without a remote, a repository is keyed by its main worktree, which
+    // linked worktrees share.
+    let rooted = group_with_path(&groups, &local.join("a"));
+    assert_eq!(rooted.remote, None);
+    assert_eq!(
+        rooted.root.as_deref(),
+        Some(local.to_string_lossy().as_ref())
+    );
+    assert_eq!(
+        workdir_paths(rooted),
+        [local.join("a"), local.join("b"), base.join("local-wt")]
+    );
+
+    let outside = group_with_path(&groups, &plain);
+    assert_eq!(outside.root, None);
+    assert_eq!(outside.remote, None);
+    assert_eq!(outside.workdirs[0].worktree, None);
+    assert_eq!(outside.totals.session_count, 1);
+}
@@ -0,0 +1,61 @@
+use agent_log_lib::index::SessionIndex;
+use agent_log_lib::search::{search_page, SearchRequest, DEFAULT_PAGE_SIZE};
+use agent_log_lib::sources::{ClaudeProjectsSource, SourceRegistry};
+use agent_log_lib::BlockKind;
+use std::fs;
+
+const SESSION: &str = r#"{"type":"user","uuid":"u1","parentUuid":null,"sessionId":"s1","cwd":"/home/me/app","message":{"role":"user","content":"Run the tests"},"timestamp":"2025-10-01T09:00:00.000Z"}
+{"type":"assistant","uuid":"a1","parentUuid":"u1","sessionId":"s1","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test"}}]},"timestamp":"2025-10-01T09:00:01.000Z"}
+{"type":"user","uuid":"u2","parentUuid":"a1","sessionId":"s1","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"test result: ok"}]},"timestamp":"2025-10-01T09:00:02.000Z"}
+{"type":"assistant","uuid":"a2","parentUuid":"u2","sessionId":"s1","message":{"role":"assistant","content":[{"type":"text","text":"All tests pass."}]},"timestamp":"2025-10-01T09:00:03.000Z"}
+"#;
+
+fn search(query: &str, blocks: Vec<BlockKind>) -> Vec<(String, String)> {
+    let temp = tempfile::tempdir().unwrap();
+    let project = temp.path().join("-home-me-app");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join("s1.jsonl"), SESSION).unwrap();
+
+    let mut registry = SourceRegistry::default();
+    registry.register(ClaudeProjectsSource::new(temp.path().to_path_buf()));
+    let index = SessionIndex::open_in_memory().unwrap();
+
+    let request = SearchRequest {
+        query: query.to_string(),
+        mode: Default::default(),
+        cursor: None,
+        limit: DEFAULT_PAGE_SIZE,
+        blocks,
+    };
+    search_page(&registry, &index, &request)
+        .unwrap()
+        .results
+        .into_iter()
+        .map(|r| (r.message_uuid, r.content_preview))
+        .collect()
+}
+
+#[test]
+fn exclusions_alone_skip_messages_without_text() {
+    let results = search("-zzz", vec![]);
+
+    // Newest first; the tool call and its result have no text.
+    let uuids: Vec<&str> = results.iter().map(|(uuid, _)| uuid.as_str()).collect();
+    assert_eq!(uuids, ["a2", "u1"]);
+    assert!(results.iter().all(|(_, preview)| !preview.is_empty()));
+}
+
+#[test]
+fn exclusions_remove_matching_messages() {
+    let results = search("-tests", vec![]);
+    assert!(results.is_empty());
+}
+
+#[test]
+fn filters_alone_search_the_requested_blocks() {
+    let results = search("role:user", vec![BlockKind::ToolResult]);
+
+    let uuids: Vec<&str> = results.iter().map(|(uuid, _)| uuid.as_str()).collect();
+    assert_eq!(uuids, ["u2"]);
+    assert_eq!(results[0].1, "test result: ok");
+}
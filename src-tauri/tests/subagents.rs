@@ -0,0 +1,58 @@
+use agent_log_lib::extract_text_content;
+use agent_log_lib::sources::{ClaudeProjectsSource, SourceRegistry};
+use agent_log_lib::subagents::session_subagents;
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/claude-branches")
+}
+
+fn subagents() -> Vec<agent_log_lib::subagents::Subagent> {
+    let mut registry = SourceRegistry::default();
+    registry.register(ClaudeProjectsSource::new(fixtures()));
+
+    let session = fixtures().join("-home-me-tasks/3b9e2f10-6c4d-4a8b-a1e7-0d5c9f2b7a34.jsonl");
+    session_subagents(&registry, &session).unwrap()
+}
+
+#[test]
+fn matches_sidechains_to_task_calls_by_prompt() {
+    let subagents = subagents();
+    assert_eq!(subagents.len(), 3);
+
+    let lexer = &subagents[0];
+    assert_eq!(lexer.tool_use_id.as_deref(), Some("toolu_lexer"));
+    assert_eq!(lexer.message_uuid.as_deref(), Some("m4"));
+    assert_eq!(lexer.description.as_deref(), Some("Review lexer"));
+    assert_eq!(lexer.subagent_type.as_deref(), Some("code-reviewer"));
+
+    let parser = &subagents[2];
+    assert_eq!(parser.tool_use_id.as_deref(), Some("toolu_parser"));
+    assert_eq!(parser.anchor_uuid.as_deref(), Some("m3"));
+}
+
+#[test]
+fn reads_subagent_files_as_separate_chains() {
+    let parser = &subagents()[2];
+
+    let ids: Vec<_> = parser
+        .messages
+        .iter()
+        .map(|m| m.uuid.as_deref().unwrap())
+        .collect();
+    assert_eq!(ids, ["p1", "p2", "p4"]);
+    assert_eq!(
+        extract_text_content(&parser.messages[0].message),
+        "Review src/parser.rs"
+    );
+    assert_eq!(parser.messages[1].tool_calls[0].name, "Read");
+}
+
+#[test]
+fn keeps_sidechains_without_a_matching_call() {
+    let warmup = &subagents()[1];
+
+    assert_eq!(warmup.tool_use_id, None);
+    assert_eq!(warmup.anchor_uuid, None);
+    assert_eq!(warmup.messages.len(), 1);
+}
@@ -0,0 +1,123 @@
+use agent_log_lib::config::OpenCodeConfig;
+use agent_log_lib::sources::{ClaudeProjectsSource, OpenCodeStorageSource, SourceRegistry};
+use agent_log_lib::tree::message_tree;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/claude-branches")
+}
+
+fn registry() -> SourceRegistry {
+    let mut registry = SourceRegistry::default();
+    registry.register(ClaudeProjectsSource::new(fixtures()));
+    registry
+}
+
+fn session() -> PathBuf {
+    fixtures().join("-home-me-branchy/8f4a1c2e-7d3b-4e9a-b6c5-2d1e0f9a8b7c.jsonl")
+}
+
+#[test]
+fn keeps_regenerated_and_edited_messages_as_siblings() {
+    let tree = message_tree(&registry(), &session(), None).unwrap();
+
+    let node = |id: &str| tree.nodes.iter().find(|n| n.id == id).unwrap();
+    assert_eq!(tree.roots, ["u1"]);
+    assert_eq!(node("u1").children, ["a1", "a1b"]);
+    assert_eq!(node("a1b").children, ["u2", "u2e"]);
+    assert!(node("u1").is_branch_point);
+    assert!(node("a1b").is_branch_point);
+    assert!(!node("u2").is_branch_point);
+}
+
+#[test]
+fn skips_hidden_records_when_linking_parents() {
+    let tree = message_tree(&registry(), &session(), None).unwrap();
+
+    assert!(tree.nodes.iter().all(|n| n.id != "t2"));
+    let a3 = tree.nodes.iter().find(|n| n.id == "a3").unwrap();
+    assert_eq!(a3.parent.as_deref(), Some("a2"));
+}
+
+#[test]
+fn chains_records_without_a_parent_to_the_previous_one() {
+    let tree = message_tree(&registry(), &session(), None).unwrap();
+
+    let u5 = tree.nodes.iter().find(|n| n.id == "u5").unwrap();
+    assert_eq!(u5.parent.as_deref(), Some("a4"));
+}
+
+#[test]
+fn active_branch_ends_at_the_last_message_by_default() {
+    let tree = message_tree(&registry(), &session(), None).unwrap();
+
+    assert_eq!(tree.active_path, ["u1", "a1b", "u2e", "a4", "u5", "a5"]);
+}
+
+#[test]
+fn active_branch_follows_the_most_recent_children() {
+    let registry = registry();
+
+    let from_root = message_tree(&registry, &session(), Some("u1")).unwrap();
+    assert_eq!(
+        from_root.active_path,
+        ["u1", "a1b", "u2e", "a4", "u5", "a5"]
+    );
+
+    let older = message_tree(&registry, &session(), Some("u2")).unwrap();
+    assert_eq!(older.active_path, ["u1", "a1b", "u2", "a2", "a3"]);
+
+    let abandoned = message_tree(&registry, &session(), Some("a1")).unwrap();
+    assert_eq!(abandoned.active_path, ["u1", "a1"]);
+
+    assert!(message_tree(&registry, &session(), Some("missing")).is_err());
+}
+
+#[test]
+fn chains_opencode_steps_in_creation_order() {
+    let temp = tempfile::tempdir().unwrap();
+    let storage = temp.path();
+    let message_dir = storage.join("message/ses_1");
+    fs::create_dir_all(&message_dir).unwrap();
+
+    // Steps of one reply, all answering msg_1 within the same second; the
+    // last two share a millisecond.
+    let messages = [
+        ("msg_1", "user", 1767434400000_i64, None),
+        ("msg_d", "assistant", 1767434400900, Some("msg_1")),
+        ("msg_b", "assistant", 1767434400100, Some("msg_1")),
+        ("msg_c", "assistant", 1767434400900, Some("msg_1")),
+    ];
+    for (id, role, created, parent) in messages {
+        let info = serde_json::json!({
+            "id": id,
+            "sessionID": "ses_1",
+            "role": role,
+            "time": { "created": created },
+            "parentID": parent,
+        });
+        fs::write(message_dir.join(format!("{}.json", id)), info.to_string()).unwrap();
+    }
+
+    let source = OpenCodeStorageSource::new(storage.to_path_buf(), OpenCodeConfig::default());
+    let loaded = source.storage_messages("ses_1").unwrap();
+
+    let order: Vec<(&str, Option<&str>)> = loaded
+        .iter()
+        .map(|m| (m.uuid.as_deref().unwrap(), m.parent_uuid.as_deref()))
+        .collect();
+    assert_eq!(
+        order,
+        [
+            ("msg_1", None),
+            ("msg_b", Some("msg_1")),
+            ("msg_c", Some("msg_b")),
+            ("msg_d", Some("msg_c")),
+        ]
+    );
+    assert_eq!(
+        loaded[1].timestamp.as_deref(),
+        Some("2026-01-03T10:00:00.100+00:00")
+    );
+}
@@ -0,0 +1,185 @@
+use agent_log_lib::config::OpenCodeConfig;
+use agent_log_lib::sources::OpenCodeStorageSource;
+use agent_log_lib::watch::{SessionTail, WatchEvent, WatchState};
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const FIRST: &str = r#"{"type":"user","uuid":"u1","parentUuid":null,"message":{"role":"user","content":"List the files"},"timestamp":"2025-09-12T08:00:00.000Z"}
+{"type":"assistant","uuid":"a1","parentUuid":"u1","message":{"role":"assistant","content":[{"type":"text","text":"Sure."}],"usage":{"input_tokens":100,"output_tokens":10}},"timestamp":"2025-09-12T08:00:01.000Z"}
+"#;
+
+const CALL: &str = r#"{"type":"assistant","uuid":"a2","parentUuid":"a1","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}],"usage":{"input_tokens":20,"output_tokens":5}},"timestamp":"2025-09-12T08:00:02.000Z"}
+"#;
+
+const RESULT: &str = r#"{"type":"user","uuid":"u2","parentUuid":"a2","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"main.rs"}]},"timestamp":"2025-09-12T08:00:04.000Z"}
+"#;
+
+#[test]
+fn reads_only_what_was_appended() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+    std::fs::write(&path, FIRST).unwrap();
+    let append = |text: &str| {
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    };
+
+    let mut tail = SessionTail::at_end(&path);
+
+    // The call, and half of its result, still being written.
+    append(CALL);
+    append(&RESULT[..40]);
+    let update = tail.read(&path).unwrap();
+    assert_eq!(update.messages.len(), 1);
+    assert_eq!(update.messages[0].uuid.as_deref(), Some("a2"));
+    assert_eq!(update.messages[0].tool_calls[0].output, None);
+    let usage = update.usage.unwrap();
+    assert_eq!(usage.input_tokens, 120);
+    assert_eq!(usage.output_tokens, 15);
+
+    // The call is sent again once its result is complete.
+    append(&RESULT[40..]);
+    let update = tail.read(&path).unwrap();
+
+    assert_eq!(update.messages.len(), 1);
+    assert_eq!(update.messages[0].uuid.as_deref(), Some("a2"));
+    assert_eq!(
+        update.messages[0].tool_calls[0].output.as_deref(),
+        Some("main.rs")
+    );
+    assert_eq!(update.messages[0].tool_calls[0].duration_ms, Some(2000));
+    assert!(update.usage.is_none());
+}
+
+/// Writes a JSON file under OpenCode's storage and returns its path.
+fn write_storage(root: &Path, relative: &str, value: serde_json::Value) -> PathBuf {
+    let path = root.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, value.to_string()).unwrap();
+    path
+}
+
+/// A message of `ses_1` with one text part.
+fn write_message(root: &Path, id: &str, created: i64, text: &str) -> Vec<PathBuf> {
+    let message = serde_json::json!({
+        "id": id,
+        "sessionID": "ses_1",
+        "role": "assistant",
+        "time": { "created": created },
+        "tokens": { "input": 10, "output": 2 },
+    });
+    let part = serde_json::json!({
+        "id": format!("prt_{}", id),
+        "messageID": id,
+        "type": "text",
+        "text": text,
+    });
+    vec![
+        write_storage(root, &format!("message/ses_1/{}.json", id), message),
+        write_storage(root, &format!("part/{}/prt_{}.json", id, id), part),
+    ]
+}
+
+/// Hands the paths to the state as one batch and returns what it sent.
+fn changed(state: &mut WatchState, paths: Vec<PathBuf>) -> Vec<WatchEvent> {
+    let mut events = vec![];
+    let batch: BTreeSet<PathBuf> = paths.into_iter().collect();
+    state.changed(&batch, &mut |event| events.push(event));
+    events
+}
+
+fn appended(events: &[WatchEvent]) -> Vec<&str> {
+    events
+        .iter()
+        .flat_map(|event| match event {
+            WatchEvent::MessagesAppended { messages, .. } => messages.as_slice(),
+            _ => &[],
+        })
+        .map(|m| m.uuid.as_deref().unwrap())
+        .collect()
+}
+
+#[test]
+fn follows_new_opencode_sessions_by_message_id() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("session/prj_1")).unwrap();
+    let source = OpenCodeStorageSource::new(root.to_path_buf(), OpenCodeConfig::default());
+    let mut state = WatchState::new(None, Some(source));
+
+    let info = write_storage(
+        root,
+        "session/prj_1/ses_1.json",
+        serde_json::json!({ "id": "ses_1", "title": "Watched" }),
+    );
+    let mut paths = vec![info.clone()];
+    paths.extend(write_message(root, "msg_1", 1767434400000, "First"));
+    let events = changed(&mut state, paths);
+
+    match &events[0] {
+        WatchEvent::SessionCreated {
+            project_path,
+            session_path,
+        } => {
+            assert_eq!(Path::new(project_path), root.join("session/prj_1"));
+            assert_eq!(Path::new(session_path), info);
+        }
+        other => panic!("expected SessionCreated, got {:?}", other),
+    }
+    assert_eq!(appended(&events), ["msg_1"]);
+    assert!(matches!(
+        events.last(),
+        Some(WatchEvent::TokensChanged { usage, .. }) if usage.input_tokens == 10
+    ));
+
+    // Two messages created in the same millisecond, in one batch: the
+    // session is read once, and the last message sent is sent again.
+    let mut paths = write_message(root, "msg_3", 1767434401000, "Third");
+    paths.extend(write_message(root, "msg_2", 1767434401000, "Second"));
+    let events = changed(&mut state, paths);
+    assert_eq!(appended(&events), ["msg_1", "msg_2", "msg_3"]);
+    assert_eq!(
+        events
+            .iter()
+            .filter(|e| matches!(e, WatchEvent::MessagesAppended { .. }))
+            .count(),
+        1
+    );
+
+    // A part changing without new messages sends only the last one again.
+    let part = write_storage(
+        root,
+        "part/msg_3/prt_msg_3.json",
+        serde_json::json!({ "id": "prt_msg_3", "messageID": "msg_3", "type": "text", "text": "Third, done" }),
+    );
+    let events = changed(&mut state, vec![part]);
+    assert_eq!(appended(&events), ["msg_3"]);
+    assert!(events
+        .iter()
+        .all(|e| !matches!(e, WatchEvent::SessionCreated { .. })));
+}
+
+#[test]
+fn sends_only_the_latest_message_of_a_session_seen_mid_way() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write_storage(
+        root,
+        "session/prj_1/ses_1.json",
+        serde_json::json!({ "id": "ses_1", "title": "Existing" }),
+    );
+    write_message(root, "msg_1", 1767434400000, "First");
+    let source = OpenCodeStorageSource::new(root.to_path_buf(), OpenCodeConfig::default());
+    let mut state = WatchState::new(None, Some(source));
+
+    // Only a part of a message written before watching started changes.
+    let paths = write_message(root, "msg_2", 1767434401000, "Second");
+    let events = changed(&mut state, vec![paths[1].clone()]);
+
+    assert_eq!(appended(&events), ["msg_2"]);
+    assert!(events
+        .iter()
+        .all(|e| !matches!(e, WatchEvent::SessionCreated { .. })));
+}
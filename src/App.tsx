@@ -1,33 +1,66 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useEffect, useRef, useState } from "react";
 import { Dashboard } from "./components/Dashboard";
 import { useScrollPosition } from "./hooks/useScrollPosition";
 import { TabSwitcher } from "./components/tab-switcher";
 import { BackgroundGradient } from "./components/background-gradient";
 import { Sidebar } from "./components/sidebar";
+import { workdirKey } from "./components/project-list";
 import { ContentViewer } from "./components/content-viewer";
 import { CopyFeedback } from "./components/copy-feedback";
 import { cn } from "./utils/cn";
+import { getActiveBranch, upsertMessages } from "./utils/message";
 import type {
   Project,
+  RepoGroup,
+  WorkdirGroup,
   Session,
   Message,
   SearchResult,
+  MessageTree,
+  BranchInfo,
+  Subagent,
+  SessionRecord,
+  SessionNotice,
+  SearchMode,
+  SearchError,
+  SearchEvent,
+  BlockKind,
   SessionContext,
   ViewMode,
   SidebarTab,
+  TokenTotals,
+  WatchEvent,
 } from "./types";
 
 function App() {
   const [projects, setProjects] = useState<Project[]>([]);
+  // Set while projects are shown grouped by git repository.
+  const [repoGroups, setRepoGroups] = useState<RepoGroup[] | null>(null);
   const [sessions, setSessions] = useState<Session[]>([]);
   const [messages, setMessages] = useState<Message[]>([]);
+  const [branches, setBranches] = useState<(BranchInfo | null)[]>([]);
+  const [subagents, setSubagents] = useState<Subagent[]>([]);
+  const [summaries, setSummaries] = useState<string[]>([]);
+  const [notices, setNotices] = useState<SessionNotice[]>([]);
+  const [showToolCalls, setShowToolCalls] = useState(true);
   const [selectedProject, setSelectedProject] = useState<Project | null>(null);
   const [selectedSession, setSelectedSession] = useState<Session | null>(null);
   const [loading, setLoading] = useState(false);
   const [searchQuery, setSearchQuery] = useState("");
   const [searchResults, setSearchResults] = useState<SearchResult[]>([]);
   const [isSearching, setIsSearching] = useState(false);
+  const [searchMode, setSearchMode] = useState<SearchMode>("full-text");
+  const [searchError, setSearchError] = useState<string | null>(null);
+  const [searchCursor, setSearchCursor] = useState<string | null>(null);
+  const [searchAllBlocks, setSearchAllBlocks] = useState(false);
+  const searchChannelRef = useRef<Channel<SearchEvent> | null>(null);
+  const lastSearchRef = useRef<{
+    query: string;
+    mode: SearchMode;
+    blocks: BlockKind[];
+  } | null>(null);
   const [viewMode, setViewMode] = useState<ViewMode>("messages");
   const [copyFeedback, setCopyFeedback] = useState<string | null>(null);
   const [sessionContext, setSessionContext] = useState<SessionContext | null>(
@@ -46,6 +79,50 @@ function App() {
     loadProjects();
   }, []);
 
+  // Follows the session watcher, so the open session and the lists update
+  // while an agent is still writing.
+  useEffect(() => {
+    const unlisten = listen<WatchEvent>("session-event", ({ payload }) => {
+      switch (payload.kind) {
+        case "session_created":
+          loadProjects();
+          if (selectedProject?.path === payload.project_path) {
+            invoke<Session[]>("get_sessions", {
+              projectPath: selectedProject.path,
+            })
+              .then(setSessions)
+              .catch(console.error);
+          }
+          break;
+        case "messages_appended":
+          if (selectedSession?.path === payload.session_path) {
+            setMessages((current) => upsertMessages(current, payload.messages));
+          }
+          break;
+        case "tokens_changed": {
+          const totals: TokenTotals = {
+            input_tokens: payload.input_tokens,
+            output_tokens: payload.output_tokens,
+            reasoning_tokens: payload.reasoning_tokens,
+            cache_read_tokens: payload.cache_read_tokens,
+            cache_write_tokens: payload.cache_write_tokens,
+            cost: payload.cost,
+          };
+          const update = (session: Session) =>
+            session.path === payload.session_path
+              ? { ...session, ...totals }
+              : session;
+          setSessions((current) => current.map(update));
+          setSelectedSession((current) => current && update(current));
+          break;
+        }
+      }
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [selectedProject?.path, selectedSession?.path]);
+
   async function loadProjects() {
     try {
       const result = await invoke<Project[]>("get_projects");
@@ -55,6 +132,33 @@ function App() {
     }
   }
 
+  async function toggleRepoGroups() {
+    if (repoGroups) {
+      setRepoGroups(null);
+      return;
+    }
+    try {
+      setRepoGroups(await invoke<RepoGroup[]>("get_project_groups"));
+    } catch (error) {
+      console.error("Failed to group projects:", error);
+    }
+  }
+
+  // The group already holds its sessions, so there is nothing to fetch.
+  function selectWorkdir(workdir: WorkdirGroup) {
+    setSelectedProject({
+      name: workdir.name,
+      path: workdirKey(workdir),
+      session_count: workdir.session_count,
+    });
+    setSelectedSession(null);
+    setMessages([]);
+    setSessions(workdir.sessions);
+    if (workdir.sessions.length > 0) {
+      loadMessages(workdir.sessions[0]);
+    }
+  }
+
   async function loadSessions(project: Project) {
     setLoading(true);
     setSelectedProject(project);
@@ -79,20 +183,13 @@ function App() {
     setLoading(true);
     setSelectedSession(session);
     setSessionContext(null);
+    setSubagents([]);
+    setSummaries([]);
+    setNotices([]);
     try {
-      const isOpenCodeSession = selectedProject?.name === "OpenCode Sessions";
-      
-      if (isOpenCodeSession) {
-        const result = await invoke<Message[]>("get_opencode_messages", {
-          sessionId: session.id,
-        });
-        setMessages(result);
-      } else {
-        const result = await invoke<Message[]>("get_messages", {
-          sessionPath: session.path,
-        });
-        setMessages(result);
-      }
+      await loadMessageTree(session);
+      loadSubagents(session);
+      loadRecords(session);
     } catch (error) {
       console.error("Failed to load messages:", error);
     } finally {
@@ -100,12 +197,62 @@ function App() {
     }
   }
 
+  // Shows the branch that `activeLeaf` is on, or the latest one.
+  async function loadMessageTree(session: Session, activeLeaf?: string) {
+    const tree = await invoke<MessageTree>("get_message_tree", {
+      sessionPath: session.path,
+      activeLeaf: activeLeaf ?? null,
+    });
+    const active = getActiveBranch(tree);
+    setMessages(active.messages);
+    setBranches(active.branches);
+  }
+
+  async function loadSubagents(session: Session) {
+    try {
+      const result = await invoke<Subagent[]>("get_subagents", {
+        sessionPath: session.path,
+      });
+      setSubagents(result);
+    } catch (error) {
+      console.error("Failed to load subagents:", error);
+    }
+  }
+
+  // Summaries and system notices live outside the message tree.
+  async function loadRecords(session: Session) {
+    try {
+      const records = await invoke<SessionRecord[]>("get_records", {
+        sessionPath: session.path,
+      });
+      setSummaries(
+        records.flatMap((r) => (r.kind === "summary" ? [r.summary] : [])),
+      );
+      setNotices(
+        records.filter(
+          (r): r is SessionNotice =>
+            r.kind === "system" || r.kind === "compact_boundary",
+        ),
+      );
+    } catch (error) {
+      console.error("Failed to load session records:", error);
+    }
+  }
+
+  async function selectBranch(messageId: string) {
+    if (!selectedSession) return;
+    try {
+      await loadMessageTree(selectedSession, messageId);
+    } catch (error) {
+      console.error("Failed to switch branch:", error);
+    }
+  }
+
   async function loadContext() {
-    if (!selectedSession || !selectedProject) return;
+    if (!selectedSession) return;
     try {
       const result = await invoke<SessionContext>("get_session_context", {
         sessionPath: selectedSession.path,
-        projectName: selectedProject.name,
       });
       setSessionContext(result);
     } catch (error) {
@@ -113,22 +260,73 @@ function App() {
     }
   }
 
+  // Typing a new query stops a search that is still streaming.
+  useEffect(() => {
+    if (searchChannelRef.current) {
+      searchChannelRef.current = null;
+      setIsSearching(false);
+      invoke("cancel_search").catch(console.error);
+    }
+  }, [searchQuery]);
+
   async function handleSearch(e: React.FormEvent) {
     e.preventDefault();
+    setSearchError(null);
+    setSearchResults([]);
+    setSearchCursor(null);
     if (!searchQuery.trim()) {
-      setSearchResults([]);
       return;
     }
+    lastSearchRef.current = {
+      query: searchQuery,
+      mode: searchMode,
+      blocks: searchAllBlocks
+        ? ["text", "tool_use", "tool_result", "thinking"]
+        : ["text"],
+    };
+    await streamSearch(null);
+  }
+
+  async function loadMoreSearchResults() {
+    if (searchCursor) {
+      await streamSearch(searchCursor);
+    }
+  }
+
+  async function streamSearch(cursor: string | null) {
+    if (!lastSearchRef.current) return;
+
+    const channel = new Channel<SearchEvent>();
+    searchChannelRef.current = channel;
+    channel.onmessage = (message) => {
+      if (searchChannelRef.current !== channel) return;
+      if (message.event === "results") {
+        setSearchResults((prev) => [...prev, ...message.data.results]);
+      } else if (message.event === "finished") {
+        setSearchCursor(message.data.next_cursor);
+      }
+    };
+
     setIsSearching(true);
     try {
-      const results = await invoke<SearchResult[]>("search_messages", {
-        query: searchQuery,
+      await invoke("stream_search_messages", {
+        request: { ...lastSearchRef.current, cursor },
+        onEvent: channel,
       });
-      setSearchResults(results);
     } catch (error) {
+      const searchError = error as SearchError;
+      setSearchResults([]);
+      setSearchError(
+        searchError.kind === "invalid_regex"
+          ? `Invalid regex: ${searchError.message}`
+          : searchError.message,
+      );
       console.error("Search failed:", error);
     } finally {
-      setIsSearching(false);
+      if (searchChannelRef.current === channel) {
+        searchChannelRef.current = null;
+        setIsSearching(false);
+      }
     }
   }
 
@@ -147,10 +345,15 @@ function App() {
       const session = sessionsResult.find((s) => s.id === result.session_id);
       if (session) {
         setSelectedSession(session);
-        const messagesResult = await invoke<Message[]>("get_messages", {
-          sessionPath: session.path,
-        });
-        setMessages(messagesResult);
+        setSubagents([]);
+        setSummaries([]);
+        setNotices([]);
+        loadSubagents(session);
+        loadRecords(session);
+        // Open the branch the hit is on, which may not be the latest one.
+        await loadMessageTree(session, result.message_uuid || undefined).catch(
+          () => loadMessageTree(session),
+        );
       }
     }
   }
@@ -201,11 +404,21 @@ function App() {
               setSearchQuery={setSearchQuery}
               searchResults={searchResults}
               isSearching={isSearching}
+              searchMode={searchMode}
+              setSearchMode={setSearchMode}
+              searchError={searchError}
+              searchAllBlocks={searchAllBlocks}
+              setSearchAllBlocks={setSearchAllBlocks}
+              hasMoreSearchResults={searchCursor !== null}
+              onLoadMoreSearchResults={loadMoreSearchResults}
               onSearch={handleSearch}
               onSearchResultClick={openSearchResult}
               projects={projects}
               selectedProject={selectedProject}
               onProjectClick={loadSessions}
+              repoGroups={repoGroups}
+              onToggleGroups={toggleRepoGroups}
+              onWorkdirClick={selectWorkdir}
               sessions={sessions}
               selectedSession={selectedSession}
               onSessionClick={loadMessages}
@@ -226,6 +439,13 @@ function App() {
                   viewMode={viewMode}
                   setViewMode={setViewMode}
                   messages={messages}
+                  branches={branches}
+                  onSelectBranch={selectBranch}
+                  subagents={subagents}
+                  summaries={summaries}
+                  notices={notices}
+                  showToolCalls={showToolCalls}
+                  setShowToolCalls={setShowToolCalls}
                   sessionContext={sessionContext}
                   onCopy={copyToClipboard}
                   onLoadContext={loadContext}
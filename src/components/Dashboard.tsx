@@ -37,12 +37,16 @@ type ProjectStats = {
 type DashboardStats = {
   total_input_tokens: number;
   total_output_tokens: number;
+  total_reasoning_tokens: number;
+  total_cache_read_tokens: number;
+  total_cache_write_tokens: number;
   total_sessions: number;
   total_messages: number;
   daily_stats: DailyStats[];
   hourly_activity: HourlyActivity[];
   project_stats: ProjectStats[];
   estimated_cost: number;
+  recorded_cost: number;
   avg_session_minutes: number;
 };
 
@@ -153,6 +157,12 @@ export function Dashboard() {
             </span>
             <span className="text-sm text-zinc-500">output</span>
           </div>
+          <div className="mt-2 text-xs text-zinc-500 font-mono">
+            cache {formatTokens(stats.total_cache_read_tokens)} read /{" "}
+            {formatTokens(stats.total_cache_write_tokens)} write
+            {stats.total_reasoning_tokens > 0 &&
+              ` · ${formatTokens(stats.total_reasoning_tokens)} reasoning`}
+          </div>
         </div>
         <div className="flex-1 bg-white dark:bg-zinc-800 rounded-xl p-4 border border-zinc-200 dark:border-zinc-700">
           <div className="text-xs font-semibold text-zinc-500 tracking-wide mb-3">
@@ -184,6 +194,11 @@ export function Dashboard() {
           ${stats.estimated_cost.toFixed(2)}
         </div>
         <div className="text-sm opacity-85">Estimated cost</div>
+        {stats.recorded_cost > 0 && (
+          <div className="text-xs opacity-70 mt-1">
+            incl. ${stats.recorded_cost.toFixed(2)} recorded by the agents
+          </div>
+        )}
       </div>
 
       <div className="bg-white dark:bg-zinc-800 rounded-xl p-4 border border-zinc-200 dark:border-zinc-700">
@@ -1,5 +1,12 @@
 import { useMemo } from "react";
-import type { Message, SessionContext, ViewMode } from "../types";
+import type {
+  BranchInfo,
+  Message,
+  SessionContext,
+  SessionNotice,
+  Subagent,
+  ViewMode,
+} from "../types";
 import { extractCodeSnippets } from "../utils/message";
 import { MessageList } from "./message-list";
 import { CodeSnippetsView } from "./code-snippets-view";
@@ -10,6 +17,13 @@ type Props = {
   viewMode: ViewMode;
   setViewMode: (mode: ViewMode) => void;
   messages: Message[];
+  branches: (BranchInfo | null)[];
+  onSelectBranch: (messageId: string) => void;
+  subagents: Subagent[];
+  summaries: string[];
+  notices: SessionNotice[];
+  showToolCalls: boolean;
+  setShowToolCalls: (show: boolean) => void;
   sessionContext: SessionContext | null;
   onCopy: (text: string) => void;
   onLoadContext: () => void;
@@ -19,6 +33,13 @@ export const ContentViewer = ({
   viewMode,
   setViewMode,
   messages,
+  branches,
+  onSelectBranch,
+  subagents,
+  summaries,
+  notices,
+  showToolCalls,
+  setShowToolCalls,
   sessionContext,
   onCopy,
   onLoadContext,
@@ -34,6 +55,16 @@ export const ContentViewer = ({
           {viewMode === "context" && "session context"}
         </h2>
         <div className="flex gap-1">
+          {viewMode === "messages" && (
+            <label className="flex items-center gap-1.5 mr-2 text-xs font-mono text-zinc-600 dark:text-zinc-400">
+              <input
+                type="checkbox"
+                checked={showToolCalls}
+                onChange={(e) => setShowToolCalls(e.target.checked)}
+              />
+              tool calls
+            </label>
+          )}
           {(["messages", "snippets", "context"] as const).map((mode) => (
             <button
               key={mode}
@@ -54,7 +85,24 @@ export const ContentViewer = ({
         </div>
       </div>
 
-      {viewMode === "messages" && <MessageList messages={messages} />}
+      {viewMode === "messages" && summaries.length > 0 && (
+        <div className="mb-4 text-sm text-zinc-600 dark:text-zinc-400">
+          {summaries.map((summary, i) => (
+            <p key={i}>{summary}</p>
+          ))}
+        </div>
+      )}
+
+      {viewMode === "messages" && (
+        <MessageList
+          messages={messages}
+          branches={branches}
+          onSelectBranch={onSelectBranch}
+          subagents={subagents}
+          notices={notices}
+          showToolCalls={showToolCalls}
+        />
+      )}
 
       {viewMode === "snippets" && (
         <CodeSnippetsView snippets={codeSnippets} onCopy={onCopy} />
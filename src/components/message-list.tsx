@@ -1,56 +1,271 @@
-import type { Message } from "../types";
+import { Fragment } from "react";
+import type {
+  BranchInfo,
+  Message,
+  SessionNotice,
+  Subagent,
+  ToolCall,
+} from "../types";
 import { MessageRenderer } from "./MessageRenderer";
-import { getMessageText } from "../utils/message";
+import { getMessageText, placeNotices } from "../utils/message";
 import { cn } from "../utils/cn";
 
 type Props = {
   messages: Message[];
+  branches: (BranchInfo | null)[];
+  onSelectBranch: (messageId: string) => void;
+  subagents?: Subagent[];
+  notices?: SessionNotice[];
+  showToolCalls?: boolean;
 };
 
-export const MessageList = ({ messages }: Props) => {
+export const MessageList = ({
+  messages,
+  branches,
+  onSelectBranch,
+  subagents = [],
+  notices = [],
+  showToolCalls = true,
+}: Props) => {
+  const noticesBefore = placeNotices(messages, notices);
+  const shown = new Set(messages.map((msg) => msg.uuid));
+  // Subagents whose spawning call is not on the displayed branch go last.
+  const unanchored = subagents.filter(
+    (agent) => !agent.anchor_uuid || !shown.has(agent.anchor_uuid)
+  );
+
   return (
     <div className="flex flex-col gap-3">
       {messages.map((msg, idx) => {
         const isUser = msg.message?.role === "user";
-        
+        const branch = branches[idx];
+        const spawned = msg.uuid
+          ? subagents.filter((agent) => agent.anchor_uuid === msg.uuid)
+          : [];
+        const text = getMessageText(msg);
+        const toolCalls = showToolCalls ? msg.toolCalls ?? [] : [];
+        // Turns that only called tools are left out when tool calls are hidden.
+        const hidden = !text.trim() && toolCalls.length === 0;
+
         return (
-          <div
-            key={msg.uuid || idx}
-            className={cn(
-              "max-w-[85%] min-w-0 px-4 py-3 rounded-2xl overflow-hidden",
-              isUser
-                ? "self-end bg-zinc-700 text-white rounded-br-sm dark:bg-zinc-600"
-                : "self-start bg-white dark:bg-zinc-800 rounded-bl-sm border border-zinc-200 dark:border-zinc-700"
-            )}
-          >
-            <div className="flex justify-between items-center gap-3 mb-1.5">
-              <span
+          <Fragment key={msg.uuid || idx}>
+            {noticesBefore[idx].map((notice, i) => (
+              <Notice key={i} notice={notice} />
+            ))}
+            {!hidden && (
+              <div
                 className={cn(
-                  "text-xs font-semibold opacity-70",
+                  "max-w-[85%] min-w-0 px-4 py-3 rounded-2xl overflow-hidden",
                   isUser
-                    ? "text-white/80"
-                    : "text-zinc-600 dark:text-zinc-400"
+                    ? "self-end bg-zinc-700 text-white rounded-br-sm dark:bg-zinc-600"
+                    : "self-start bg-white dark:bg-zinc-800 rounded-bl-sm border border-zinc-200 dark:border-zinc-700"
                 )}
               >
-                {msg.message?.role && msg.message.role.charAt(0).toUpperCase() + msg.message.role.slice(1)}
-              </span>
-              <span
-                className={cn(
-                  "text-[10px] opacity-60",
-                  isUser && "text-white/70"
+                <div className="flex justify-between items-center gap-3 mb-1.5">
+                  <span
+                    className={cn(
+                      "text-xs font-semibold opacity-70",
+                      isUser
+                        ? "text-white/80"
+                        : "text-zinc-600 dark:text-zinc-400"
+                    )}
+                  >
+                    {msg.message?.role && msg.message.role.charAt(0).toUpperCase() + msg.message.role.slice(1)}
+                  </span>
+                  {branch && (
+                    <BranchSwitcher
+                      branch={branch}
+                      isUser={isUser}
+                      onSelect={onSelectBranch}
+                    />
+                  )}
+                  <span
+                    className={cn(
+                      "text-[10px] opacity-60",
+                      isUser && "text-white/70"
+                    )}
+                  >
+                    {msg.timestamp
+                      ? new Date(msg.timestamp).toLocaleTimeString()
+                      : ""}
+                  </span>
+                </div>
+                {text.trim() && (
+                  <div className="text-sm leading-relaxed min-w-0">
+                    <MessageRenderer content={text} />
+                  </div>
                 )}
-              >
-                {msg.timestamp
-                  ? new Date(msg.timestamp).toLocaleTimeString()
-                  : ""}
-              </span>
-            </div>
-            <div className="text-sm leading-relaxed min-w-0">
-              <MessageRenderer content={getMessageText(msg)} />
-            </div>
-          </div>
+                {toolCalls.map((call) => (
+                  <ToolCallView key={call.id} call={call} isUser={isUser} />
+                ))}
+              </div>
+            )}
+            {spawned.map((agent, i) => (
+              <SubagentThread
+                key={agent.tool_use_id || i}
+                agent={agent}
+                showToolCalls={showToolCalls}
+              />
+            ))}
+          </Fragment>
         );
       })}
+      {noticesBefore[messages.length].map((notice, i) => (
+        <Notice key={`end-${i}`} notice={notice} />
+      ))}
+      {unanchored.map((agent, i) => (
+        <SubagentThread
+          key={agent.tool_use_id || `unanchored-${i}`}
+          agent={agent}
+          showToolCalls={showToolCalls}
+        />
+      ))}
     </div>
   );
 };
+
+// A compaction or system message, shown as a divider between messages.
+const Notice = ({ notice }: { notice: SessionNotice }) => {
+  const text =
+    notice.kind === "compact_boundary"
+      ? `Conversation compacted${
+          notice.compactMetadata?.preTokens
+            ? ` (${notice.compactMetadata.preTokens.toLocaleString()} tokens)`
+            : ""
+        }`
+      : notice.content || notice.subtype || "System message";
+
+  return (
+    <div
+      className={cn(
+        "flex items-center gap-3 text-[11px] text-zinc-500 dark:text-zinc-400",
+        notice.kind === "system" &&
+          notice.level === "error" &&
+          "text-red-600 dark:text-red-400"
+      )}
+    >
+      <div className="flex-1 border-t border-dashed border-zinc-300 dark:border-zinc-600" />
+      <span className="max-w-[70%] truncate" title={text}>
+        {text}
+      </span>
+      <div className="flex-1 border-t border-dashed border-zinc-300 dark:border-zinc-600" />
+    </div>
+  );
+};
+
+// A tool call with its input and output, collapsed to one line by default.
+const ToolCallView = ({ call, isUser }: { call: ToolCall; isUser: boolean }) => {
+  const status =
+    call.output === null ? "no result" : call.is_error ? "error" : null;
+
+  return (
+    <details
+      className={cn(
+        "mt-2 text-xs rounded-lg border px-2 py-1",
+        call.is_error
+          ? "border-red-300 dark:border-red-800"
+          : "border-zinc-200 dark:border-zinc-700",
+        isUser && "border-white/20"
+      )}
+    >
+      <summary className="cursor-pointer font-mono">
+        {call.name}
+        {status && (
+          <span
+            className={cn(
+              "ml-2",
+              call.is_error ? "text-red-600 dark:text-red-400" : "opacity-60"
+            )}
+          >
+            {status}
+          </span>
+        )}
+        {call.duration_ms !== null && (
+          <span className="ml-2 opacity-60">
+            {(call.duration_ms / 1000).toFixed(1)}s
+          </span>
+        )}
+      </summary>
+      <pre className="mt-1 whitespace-pre-wrap break-all opacity-80">
+        {JSON.stringify(call.input, null, 2)}
+      </pre>
+      {call.output !== null && (
+        <pre className="mt-1 pt-1 border-t border-zinc-200 dark:border-zinc-700 whitespace-pre-wrap break-all max-h-64 overflow-auto">
+          {call.output}
+        </pre>
+      )}
+    </details>
+  );
+};
+
+// A subagent's own conversation, collapsed under the call that started it.
+const SubagentThread = ({
+  agent,
+  showToolCalls,
+}: {
+  agent: Subagent;
+  showToolCalls: boolean;
+}) => {
+  const title = agent.description || "Subagent";
+
+  return (
+    <details className="self-start max-w-[85%] w-full ml-6 border-l-2 border-zinc-300 dark:border-zinc-600 pl-3">
+      <summary className="cursor-pointer text-xs text-zinc-600 dark:text-zinc-400">
+        Subagent: {title}
+        {agent.subagent_type && ` (${agent.subagent_type})`} &mdash;{" "}
+        {agent.messages.length} messages
+      </summary>
+      <div className="mt-2">
+        <MessageList
+          messages={agent.messages}
+          branches={[]}
+          onSelectBranch={() => {}}
+          showToolCalls={showToolCalls}
+        />
+      </div>
+    </details>
+  );
+};
+
+// Steps between the alternatives a conversation took from the same message.
+const BranchSwitcher = ({
+  branch,
+  isUser,
+  onSelect,
+}: {
+  branch: BranchInfo;
+  isUser: boolean;
+  onSelect: (messageId: string) => void;
+}) => {
+  const { siblings, index } = branch;
+
+  return (
+    <span
+      className={cn(
+        "flex items-center gap-1 text-[10px] opacity-70",
+        isUser ? "text-white/80" : "text-zinc-600 dark:text-zinc-400"
+      )}
+      title="This message was edited or regenerated"
+    >
+      <button
+        type="button"
+        className="px-1 disabled:opacity-30"
+        disabled={index <= 0}
+        onClick={() => onSelect(siblings[index - 1])}
+        aria-label="Previous branch"
+      >
+        &lsaquo;
+      </button>
+      {index + 1}/{siblings.length}
+      <button
+        type="button"
+        className="px-1 disabled:opacity-30"
+        disabled={index >= siblings.length - 1}
+        onClick={() => onSelect(siblings[index + 1])}
+        aria-label="Next branch"
+      >
+        &rsaquo;
+      </button>
+    </span>
+  );
+};
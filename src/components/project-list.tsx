@@ -1,40 +1,109 @@
-import type { Project } from "../types";
+import type { Project, RepoGroup, WorkdirGroup } from "../types";
 import { cn } from "../utils/cn";
 
 type Props = {
   projects: Project[];
   selectedProject: Project | null;
   onProjectClick: (project: Project) => void;
+  repoGroups: RepoGroup[] | null;
+  onToggleGroups: () => void;
+  onWorkdirClick: (workdir: WorkdirGroup) => void;
 };
 
+const formatTokens = (tokens: number) =>
+  tokens >= 1_000_000
+    ? `${(tokens / 1_000_000).toFixed(1)}M`
+    : tokens >= 1_000
+      ? `${(tokens / 1_000).toFixed(1)}k`
+      : String(tokens);
+
+// Workdir groups are selected as a project whose path is the directory.
+export const workdirKey = (workdir: WorkdirGroup) =>
+  workdir.path ?? workdir.name;
+
 export const ProjectList = ({
   projects,
   selectedProject,
   onProjectClick,
+  repoGroups,
+  onToggleGroups,
+  onWorkdirClick,
 }: Props) => {
   return (
     <>
-      <h2 className="px-4 py-3 text-xs font-semibold text-zinc-500 dark:text-zinc-400 border-b border-zinc-200 dark:border-zinc-700 font-mono">
-        Projects
+      <h2 className="px-4 py-3 text-xs font-semibold text-zinc-500 dark:text-zinc-400 border-b border-zinc-200 dark:border-zinc-700 font-mono flex justify-between items-center">
+        <span>Projects</span>
+        <button
+          className="text-xs font-normal hover:text-zinc-800 dark:hover:text-zinc-200"
+          onClick={onToggleGroups}
+        >
+          {repoGroups ? "By source" : "By repository"}
+        </button>
       </h2>
       <div className="flex-1 overflow-y-auto">
-        {projects.map((project) => (
-          <div
-            key={project.path}
-            className={cn(
-              "px-4 py-3 cursor-pointer flex justify-between items-center border-b border-zinc-200 dark:border-zinc-800 hover:bg-white dark:hover:bg-zinc-800",
-              selectedProject?.path === project.path && "bg-white dark:bg-zinc-800"
-            )}
-            onClick={() => onProjectClick(project)}
-          >
-            <span className="text-sm font-medium truncate flex-1">
-              {project.name}
-            </span>
-            <span className="text-xs text-zinc-500 bg-zinc-200 dark:bg-zinc-800 px-2 py-0.5 rounded-full ml-2">
-              {project.session_count}
-            </span>
-          </div>
-        ))}
+        {repoGroups
+          ? repoGroups.map((repo) => (
+              <div
+                key={repo.root ?? repo.name}
+                className="border-b border-zinc-200 dark:border-zinc-800"
+              >
+                <div
+                  className="px-4 pt-3 pb-1 flex justify-between items-center"
+                  title={repo.remote ?? undefined}
+                >
+                  <span className="text-sm font-semibold truncate flex-1">
+                    {repo.name}
+                  </span>
+                  <span className="text-xs text-zinc-500 ml-2">
+                    {formatTokens(repo.input_tokens + repo.output_tokens)}
+                  </span>
+                  <span className="text-xs text-zinc-500 bg-zinc-200 dark:bg-zinc-800 px-2 py-0.5 rounded-full ml-2">
+                    {repo.session_count}
+                  </span>
+                </div>
+                {repo.workdirs.map((workdir) => (
+                  <div
+                    key={workdirKey(workdir)}
+                    className={cn(
+                      "pl-8 pr-4 py-2 cursor-pointer flex justify-between items-center hover:bg-white dark:hover:bg-zinc-800",
+                      selectedProject?.path === workdirKey(workdir) &&
+                        "bg-white dark:bg-zinc-800",
+                    )}
+                    onClick={() => onWorkdirClick(workdir)}
+                  >
+                    <span className="text-sm truncate flex-1">
+                      {workdir.name}
+                    </span>
+                    <span className="text-xs text-zinc-500 ml-2">
+                      {formatTokens(
+                        workdir.input_tokens + workdir.output_tokens,
+                      )}
+                    </span>
+                    <span className="text-xs text-zinc-500 bg-zinc-200 dark:bg-zinc-800 px-2 py-0.5 rounded-full ml-2">
+                      {workdir.session_count}
+                    </span>
+                  </div>
+                ))}
+              </div>
+            ))
+          : projects.map((project) => (
+              <div
+                key={project.path}
+                className={cn(
+                  "px-4 py-3 cursor-pointer flex justify-between items-center border-b border-zinc-200 dark:border-zinc-800 hover:bg-white dark:hover:bg-zinc-800",
+                  selectedProject?.path === project.path &&
+                    "bg-white dark:bg-zinc-800",
+                )}
+                onClick={() => onProjectClick(project)}
+              >
+                <span className="text-sm font-medium truncate flex-1">
+                  {project.name}
+                </span>
+                <span className="text-xs text-zinc-500 bg-zinc-200 dark:bg-zinc-800 px-2 py-0.5 rounded-full ml-2">
+                  {project.session_count}
+                </span>
+              </div>
+            ))}
       </div>
     </>
   );
@@ -1,11 +1,38 @@
-import type { SearchResult } from "../types";
+import type {
+  BlockKind,
+  MatchSpan,
+  SearchMode,
+  SearchResult,
+} from "../types";
 import { cn } from "../utils/cn";
 
+const SEARCH_MODES: { value: SearchMode; label: string }[] = [
+  { value: "full-text", label: "Words" },
+  { value: "substring", label: "Substring" },
+  { value: "case-sensitive", label: "Match case" },
+  { value: "whole-word", label: "Whole word" },
+  { value: "regex", label: "Regex" },
+];
+
+const BLOCK_LABELS: Record<BlockKind, string> = {
+  text: "Text",
+  tool_use: "Tool call",
+  tool_result: "Tool output",
+  thinking: "Thinking",
+};
+
 type Props = {
   searchQuery: string;
   setSearchQuery: (query: string) => void;
   searchResults: SearchResult[];
   isSearching: boolean;
+  searchMode: SearchMode;
+  setSearchMode: (mode: SearchMode) => void;
+  searchError: string | null;
+  searchAllBlocks: boolean;
+  setSearchAllBlocks: (value: boolean) => void;
+  hasMoreResults: boolean;
+  onLoadMore: () => void;
   onSubmit: (e: React.FormEvent) => void;
   onResultClick: (result: SearchResult) => void;
 };
@@ -15,6 +42,13 @@ export const SearchBar = ({
   setSearchQuery,
   searchResults,
   isSearching,
+  searchMode,
+  setSearchMode,
+  searchError,
+  searchAllBlocks,
+  setSearchAllBlocks,
+  hasMoreResults,
+  onLoadMore,
   onSubmit,
   onResultClick,
 }: Props) => {
@@ -25,14 +59,39 @@ export const SearchBar = ({
         onSubmit={onSubmit}
         role="search"
       >
-        <input
-          type="text"
-          className="w-full px-3 py-2 text-sm bg-white dark:bg-zinc-800 border border-zinc-200 dark:border-zinc-700 rounded-lg focus:outline-none focus:border-zinc-400 dark:focus:border-zinc-600"
-          placeholder="Search messages"
-          aria-label="Search messages"
-          value={searchQuery}
-          onChange={(e) => setSearchQuery(e.target.value)}
-        />
+        <div className="flex gap-2">
+          <input
+            type="text"
+            className="flex-1 min-w-0 px-3 py-2 text-sm bg-white dark:bg-zinc-800 border border-zinc-200 dark:border-zinc-700 rounded-lg focus:outline-none focus:border-zinc-400 dark:focus:border-zinc-600"
+            placeholder="Search messages (role:, project:, after:, tool:, model:)"
+            aria-label="Search messages"
+            value={searchQuery}
+            onChange={(e) => setSearchQuery(e.target.value)}
+          />
+          <select
+            className="px-2 py-2 text-xs bg-white dark:bg-zinc-800 border border-zinc-200 dark:border-zinc-700 rounded-lg focus:outline-none focus:border-zinc-400 dark:focus:border-zinc-600"
+            aria-label="Search mode"
+            value={searchMode}
+            onChange={(e) => setSearchMode(e.target.value as SearchMode)}
+          >
+            {SEARCH_MODES.map((mode) => (
+              <option key={mode.value} value={mode.value}>
+                {mode.label}
+              </option>
+            ))}
+          </select>
+        </div>
+        <label className="flex items-center gap-2 mt-2 text-xs text-zinc-500 dark:text-zinc-400">
+          <input
+            type="checkbox"
+            checked={searchAllBlocks}
+            onChange={(e) => setSearchAllBlocks(e.target.checked)}
+          />
+          Include tool calls, tool output and thinking
+        </label>
+        {searchError && (
+          <p className="mt-2 text-xs text-red-500 break-words">{searchError}</p>
+        )}
       </form>
 
       {searchResults.length > 0 && (
@@ -58,15 +117,32 @@ export const SearchBar = ({
                   >
                     {result.role}
                   </span>
+                  {result.block !== "text" && (
+                    <span className="text-xs text-zinc-400">
+                      {BLOCK_LABELS[result.block]}
+                    </span>
+                  )}
                   <span className="text-xs text-zinc-400">
                     {result.project_name.split("/").pop()}
                   </span>
                 </div>
                 <div className="text-xs text-zinc-500 dark:text-zinc-400 truncate">
-                  {result.content_preview}
+                  <HighlightedText
+                    text={result.content_preview}
+                    highlights={result.highlights}
+                  />
                 </div>
               </div>
             ))}
+            {hasMoreResults && !isSearching && (
+              <button
+                type="button"
+                className="w-full px-4 py-2 text-xs text-zinc-500 dark:text-zinc-400 hover:bg-white dark:hover:bg-zinc-800"
+                onClick={onLoadMore}
+              >
+                Load more results
+              </button>
+            )}
           </div>
         </>
       )}
@@ -79,3 +155,33 @@ export const SearchBar = ({
     </>
   );
 };
+
+const HighlightedText = ({
+  text,
+  highlights,
+}: {
+  text: string;
+  highlights: MatchSpan[];
+}) => {
+  // Spans count characters, so index by code point rather than UTF-16 unit.
+  const chars = Array.from(text);
+  const parts: React.ReactNode[] = [];
+  let pos = 0;
+
+  highlights.forEach((span, idx) => {
+    if (span.start < pos) return;
+    parts.push(chars.slice(pos, span.start).join(""));
+    parts.push(
+      <mark
+        key={idx}
+        className="bg-yellow-200 text-zinc-900 dark:bg-yellow-500/40 dark:text-zinc-100 rounded-sm"
+      >
+        {chars.slice(span.start, span.end).join("")}
+      </mark>,
+    );
+    pos = span.end;
+  });
+  parts.push(chars.slice(pos).join(""));
+
+  return <>{parts}</>;
+};
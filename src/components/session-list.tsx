@@ -29,6 +29,11 @@ export const SessionList = ({
             onClick={() => onSessionClick(session)}
           >
             <div className="flex flex-col gap-1">
+              {session.title && (
+                <span className="text-sm truncate" title={session.title}>
+                  {session.title}
+                </span>
+              )}
               <span className="text-xs text-zinc-600 dark:text-zinc-400">
                 {formatDate(session.modified)}
               </span>
@@ -1,4 +1,11 @@
-import type { Project, Session, SearchResult } from "../types";
+import type {
+  Project,
+  RepoGroup,
+  Session,
+  SearchResult,
+  SearchMode,
+  WorkdirGroup,
+} from "../types";
 import { SearchBar } from "./search-bar";
 import { ProjectList } from "./project-list";
 import { SessionList } from "./session-list";
@@ -8,11 +15,21 @@ type Props = {
   setSearchQuery: (query: string) => void;
   searchResults: SearchResult[];
   isSearching: boolean;
+  searchMode: SearchMode;
+  setSearchMode: (mode: SearchMode) => void;
+  searchError: string | null;
+  searchAllBlocks: boolean;
+  setSearchAllBlocks: (value: boolean) => void;
+  hasMoreSearchResults: boolean;
+  onLoadMoreSearchResults: () => void;
   onSearch: (e: React.FormEvent) => void;
   onSearchResultClick: (result: SearchResult) => void;
   projects: Project[];
   selectedProject: Project | null;
   onProjectClick: (project: Project) => void;
+  repoGroups: RepoGroup[] | null;
+  onToggleGroups: () => void;
+  onWorkdirClick: (workdir: WorkdirGroup) => void;
   sessions: Session[];
   selectedSession: Session | null;
   onSessionClick: (session: Session) => void;
@@ -23,11 +40,21 @@ export const Sidebar = ({
   setSearchQuery,
   searchResults,
   isSearching,
+  searchMode,
+  setSearchMode,
+  searchError,
+  searchAllBlocks,
+  setSearchAllBlocks,
+  hasMoreSearchResults,
+  onLoadMoreSearchResults,
   onSearch,
   onSearchResultClick,
   projects,
   selectedProject,
   onProjectClick,
+  repoGroups,
+  onToggleGroups,
+  onWorkdirClick,
   sessions,
   selectedSession,
   onSessionClick,
@@ -40,6 +67,13 @@ export const Sidebar = ({
           setSearchQuery={setSearchQuery}
           searchResults={searchResults}
           isSearching={isSearching}
+          searchMode={searchMode}
+          setSearchMode={setSearchMode}
+          searchError={searchError}
+          searchAllBlocks={searchAllBlocks}
+          setSearchAllBlocks={setSearchAllBlocks}
+          hasMoreResults={hasMoreSearchResults}
+          onLoadMore={onLoadMoreSearchResults}
           onSubmit={onSearch}
           onResultClick={onSearchResultClick}
         />
@@ -48,6 +82,9 @@ export const Sidebar = ({
           projects={projects}
           selectedProject={selectedProject}
           onProjectClick={onProjectClick}
+          repoGroups={repoGroups}
+          onToggleGroups={onToggleGroups}
+          onWorkdirClick={onWorkdirClick}
         />
 
         {selectedProject && (
@@ -4,13 +4,45 @@ export type Project = {
   session_count: number;
 };
 
+// Sessions and tokens summed over a RepoGroup or WorkdirGroup.
+export type GroupTotals = {
+  session_count: number;
+  input_tokens: number;
+  output_tokens: number;
+  reasoning_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  cost: number | null;
+};
+
+// Sessions of one git repository, from get_project_groups.
+export type RepoGroup = GroupTotals & {
+  name: string;
+  remote: string | null;
+  root: string | null;
+  workdirs: WorkdirGroup[];
+};
+
+// Sessions started in the same worktree or subdirectory.
+export type WorkdirGroup = GroupTotals & {
+  name: string;
+  path: string | null;
+  worktree: string | null;
+  sessions: Session[];
+};
+
 export type Session = {
   id: string;
+  title?: string;
   path: string;
   size: number;
   modified: number;
   input_tokens: number;
   output_tokens: number;
+  reasoning_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  cost: number | null;
 };
 
 export type MessageContent = {
@@ -25,6 +57,114 @@ export type Message = {
   timestamp?: string;
   session_id?: string;
   message?: MessageContent;
+  toolCalls?: ToolCall[];
+};
+
+// A tool_use block paired with the tool_result that answered it.
+export type ToolCall = {
+  id: string;
+  name: string;
+  input: unknown;
+  output: string | null;
+  is_error: boolean;
+  duration_ms: number | null;
+};
+
+// Typed message content, as returned by `get_records`.
+export type ContentBlock =
+  | { type: "text"; text: string }
+  | { type: "thinking"; thinking: string }
+  | { type: "tool_use"; id: string; name: string; input: unknown }
+  | {
+      type: "tool_result";
+      tool_use_id: string;
+      content: unknown;
+      is_error: boolean;
+    }
+  | { type: "image"; source: unknown }
+  | { type: "unknown"; raw: unknown };
+
+export type ConversationRecord = {
+  uuid: string | null;
+  parentUuid: string | null;
+  timestamp: string | null;
+  sessionId: string | null;
+  isSidechain: boolean;
+  model: string | null;
+  usage: { input_tokens: number | null; output_tokens: number | null } | null;
+  content: ContentBlock[];
+};
+
+// One line of a session file, by kind. Kinds the backend does not know are
+// passed through as `unknown` with the original JSON.
+export type SessionRecord =
+  | ({ kind: "user" } & ConversationRecord)
+  | ({ kind: "assistant" } & ConversationRecord)
+  | { kind: "summary"; summary: string; leafUuid: string | null }
+  | {
+      kind: "system";
+      uuid: string | null;
+      parentUuid: string | null;
+      timestamp: string | null;
+      subtype: string | null;
+      level: string | null;
+      content: string | null;
+    }
+  | {
+      kind: "compact_boundary";
+      uuid: string | null;
+      logicalParentUuid: string | null;
+      timestamp: string | null;
+      content: string | null;
+      compactMetadata: {
+        trigger: string | null;
+        preTokens: number | null;
+      } | null;
+    }
+  | {
+      kind: "file_history_snapshot";
+      messageId: string | null;
+      isSnapshotUpdate: boolean;
+      snapshot: {
+        timestamp: string | null;
+        trackedFileBackups: Record<string, unknown>;
+      } | null;
+    }
+  | { kind: "unknown"; raw: unknown };
+
+// Session records shown between messages rather than as messages.
+export type SessionNotice = Extract<
+  SessionRecord,
+  { kind: "system" } | { kind: "compact_boundary" }
+>;
+
+export type Subagent = {
+  tool_use_id: string | null;
+  message_uuid: string | null;
+  anchor_uuid: string | null;
+  description: string | null;
+  subagent_type: string | null;
+  messages: Message[];
+};
+
+export type TreeNode = {
+  id: string;
+  parent: string | null;
+  children: string[];
+  is_branch_point: boolean;
+  message: Message;
+};
+
+export type MessageTree = {
+  nodes: TreeNode[];
+  roots: string[];
+  active_path: string[];
+};
+
+// Where a message on the active branch sits among its alternatives.
+export type BranchInfo = {
+  siblings: string[];
+  index: number;
 };
 
 export type SearchResult = {
@@ -34,10 +174,65 @@ export type SearchResult = {
   session_path: string;
   message_uuid: string;
   role: string;
+  block: BlockKind;
   content_preview: string;
+  highlights: MatchSpan[];
   timestamp: string;
+  score: number;
+};
+
+export type MatchSpan = {
+  start: number;
+  end: number;
+};
+
+export type BlockKind = "text" | "tool_use" | "tool_result" | "thinking";
+
+export type SearchMode =
+  | "full-text"
+  | "substring"
+  | "case-sensitive"
+  | "regex"
+  | "whole-word";
+
+export type SearchEvent =
+  | { event: "results"; data: { results: SearchResult[] } }
+  | { event: "finished"; data: { next_cursor: string | null } }
+  | { event: "cancelled" };
+
+// Sent by the session watcher as the "session-event" event.
+export type WatchEvent =
+  | { kind: "session_created"; project_path: string; session_path: string }
+  | { kind: "messages_appended"; session_path: string; messages: Message[] }
+  | ({ kind: "tokens_changed"; session_path: string } & TokenTotals);
+
+export type TokenTotals = {
+  input_tokens: number;
+  output_tokens: number;
+  reasoning_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  cost: number | null;
 };
 
+export type SearchError =
+  | { kind: "invalid_filter"; message: string }
+  | { kind: "invalid_regex"; pattern: string; message: string }
+  | { kind: "index"; message: string };
+
+export type LineError = {
+  line: number;
+  message: string;
+};
+
+export type OpenCodeError =
+  | { kind: "binary_not_found"; configured: string | null }
+  | { kind: "spawn"; binary: string; message: string }
+  | { kind: "timed_out"; seconds: number }
+  | { kind: "failed"; code: number | null; stderr: string }
+  | { kind: "invalid_export"; message: string }
+  | { kind: "io"; message: string };
+
 export type FileChange = {
   file_path: string;
   action: string;
@@ -54,7 +249,7 @@ export type GitCommit = {
 export type SessionContext = {
   file_changes: FileChange[];
   git_commits: GitCommit[];
-  project_path: string;
+  project_path: string | null;
 };
 
 export type CodeSnippet = {
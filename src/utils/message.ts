@@ -1,4 +1,11 @@
-import type { Message, CodeSnippet } from "../types";
+import type {
+  Message,
+  CodeSnippet,
+  MessageTree,
+  TreeNode,
+  BranchInfo,
+  SessionNotice,
+} from "../types";
 
 export const getMessageText = (message: Message): string => {
   const content = message.message?.content;
@@ -31,3 +38,61 @@ export const extractCodeSnippets = (messages: Message[]): CodeSnippet[] => {
   }
   return snippets;
 };
+
+// Adds messages the watcher sent, replacing the ones it sent again.
+export const upsertMessages = (
+  current: Message[],
+  incoming: Message[],
+): Message[] => {
+  const next = [...current];
+  for (const msg of incoming) {
+    const index = msg.uuid ? next.findIndex((m) => m.uuid === msg.uuid) : -1;
+    if (index >= 0) {
+      next[index] = msg;
+    } else {
+      next.push(msg);
+    }
+  }
+  return next;
+};
+
+export const getActiveBranch = (
+  tree: MessageTree,
+): { messages: Message[]; branches: (BranchInfo | null)[] } => {
+  const nodes = new Map<string, TreeNode>(tree.nodes.map((n) => [n.id, n]));
+  const path = tree.active_path
+    .map((id) => nodes.get(id))
+    .filter((n): n is TreeNode => n !== undefined);
+
+  return {
+    messages: path.map((n) => n.message),
+    branches: path.map((n) => {
+      const siblings = n.parent
+        ? nodes.get(n.parent)?.children
+        : tree.roots;
+      if (!siblings || siblings.length < 2) return null;
+      return { siblings, index: siblings.indexOf(n.id) };
+    }),
+  };
+};
+
+// Buckets notices by the message they come before, going by timestamp. The
+// extra last bucket holds the ones after every message.
+export const placeNotices = (
+  messages: Message[],
+  notices: SessionNotice[],
+): SessionNotice[][] => {
+  const buckets: SessionNotice[][] = messages.map(() => []);
+  buckets.push([]);
+
+  for (const notice of notices) {
+    const at = notice.timestamp
+      ? messages.findIndex(
+          (msg) => !!msg.timestamp && msg.timestamp > notice.timestamp!,
+        )
+      : -1;
+    buckets[at === -1 ? messages.length : at].push(notice);
+  }
+
+  return buckets;
+};